@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+/// The expected answers of the puzzle inputs, read from a small subset of TOML:
+///
+/// ```toml
+/// [day07]
+/// part1 = "tknk"
+/// part2 = 60
+/// ```
+#[derive(Debug, Default, PartialEq)]
+pub struct Answers {
+    answers: HashMap<(u32, u8), String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Pass,
+    Fail(String), // the expected answer
+    Missing,
+}
+
+// a `#` starts a comment, unless it is inside a quoted string
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+impl Answers {
+    pub fn parse(s: &str) -> Result<Answers, String> {
+        let mut answers = HashMap::new();
+        let mut day = None;
+
+        for (n, line) in s.lines().enumerate() {
+            let error = |msg: &str| format!("line {}: {}", n + 1, msg);
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                let table = &line[1..line.len() - 1];
+                day = match table.strip_prefix("day").map(str::parse::<u32>) {
+                    Some(Ok(d)) => Some(d),
+                    _ => return Err(error("expected a table like `[day07]`")),
+                };
+                continue;
+            }
+
+            let mut key_value = line.splitn(2, '=').map(str::trim);
+            let part = match key_value.next() {
+                Some("part1") => 1,
+                Some("part2") => 2,
+                _ => return Err(error("expected `part1` or `part2`")),
+            };
+            let value = match key_value.next() {
+                Some(v) if v.len() >= 2 && v.starts_with('"') && v.ends_with('"') => {
+                    &v[1..v.len() - 1]
+                }
+                Some(v) if !v.is_empty() && v.parse::<i64>().is_ok() => v,
+                _ => return Err(error("expected a string or an integer")),
+            };
+            let day = day.ok_or_else(|| error("expected a `[dayNN]` table first"))?;
+            answers.insert((day, part), value.to_string());
+        }
+
+        Ok(Answers { answers })
+    }
+
+    pub fn check(&self, day: u32, part: u8, answer: &str) -> Verdict {
+        match self.answers.get(&(day, part)) {
+            None => Verdict::Missing,
+            Some(expected) if expected == answer => Verdict::Pass,
+            Some(expected) => Verdict::Fail(expected.clone()),
+        }
+    }
+
+    /// Whether there is an answer to check for this part.
+    pub fn expects(&self, day: u32, part: u8) -> bool {
+        self.answers.contains_key(&(day, part))
+    }
+}
+
+#[test]
+fn test_parse() {
+    let answers = Answers::parse(
+        r##"
+# a comment
+[day07]
+part1 = "tknk"  # the bottom program
+part2 = 60
+
+[day10]
+part2 = "a2582a3a0e66e6e86e3812dcb672a272"
+
+[day22]
+part1 = "#.#"  # a "quoted" comment
+"##,
+    )
+    .unwrap();
+
+    assert_eq!(answers.check(7, 1, "tknk"), Verdict::Pass);
+    assert_eq!(answers.check(7, 2, "61"), Verdict::Fail(String::from("60")));
+    assert_eq!(answers.check(10, 1, "12"), Verdict::Missing);
+    assert_eq!(answers.check(1, 1, "3"), Verdict::Missing);
+    assert_eq!(answers.check(22, 1, "#.#"), Verdict::Pass);
+    assert!(answers.expects(10, 2) && !answers.expects(10, 1));
+}
+
+#[test]
+fn test_parse_error() {
+    assert_eq!(
+        Answers::parse("part1 = 3"),
+        Err(String::from("line 1: expected a `[dayNN]` table first"))
+    );
+    assert_eq!(
+        Answers::parse("[day01]\npart3 = 3"),
+        Err(String::from("line 2: expected `part1` or `part2`"))
+    );
+    assert_eq!(
+        Answers::parse("[day01]\npart1 = three"),
+        Err(String::from("line 2: expected a string or an integer"))
+    );
+    assert_eq!(
+        Answers::parse("[first]"),
+        Err(String::from("line 1: expected a table like `[day07]`"))
+    );
+}
@@ -0,0 +1,217 @@
+use aoc::{Puzzle, Run};
+use std::fmt::Write;
+use std::time::Duration;
+
+/// The spread of the times measured for one step over several runs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    fn new(mut samples: Vec<Duration>) -> Self {
+        samples.sort();
+        Stats {
+            min: samples[0],
+            median: samples[samples.len() / 2],
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+/// The times of every step of a day.
+#[derive(Debug)]
+pub struct Bench {
+    pub day: u32,
+    pub title: &'static str,
+    pub runs: usize,
+    pub parse: Stats,
+    pub parts: Vec<(u8, Stats)>,
+}
+
+impl Bench {
+    pub fn new(puzzle: &dyn Puzzle, runs: &[Run]) -> Self {
+        let parse = Stats::new(runs.iter().map(|r| r.parse).collect());
+        let parts = runs[0]
+            .solutions
+            .iter()
+            .enumerate()
+            .map(|(i, a)| {
+                let samples = runs.iter().map(|r| r.solutions[i].duration).collect();
+                (a.part, Stats::new(samples))
+            })
+            .collect();
+
+        Bench {
+            day: puzzle.day(),
+            title: puzzle.title(),
+            runs: runs.len(),
+            parse,
+            parts,
+        }
+    }
+
+    pub fn part(&self, part: u8) -> Option<Stats> {
+        self.parts.iter().find(|(p, _)| *p == part).map(|(_, s)| *s)
+    }
+
+    pub fn total(&self) -> Duration {
+        self.parse.median + self.parts.iter().map(|(_, s)| s.median).sum::<Duration>()
+    }
+
+    // every step, named as in the report
+    fn steps(&self) -> impl Iterator<Item = (String, Stats)> + '_ {
+        let parts = self.parts.iter().map(|(p, s)| (format!("part{}", p), *s));
+        Some((String::from("parse"), self.parse))
+            .into_iter()
+            .chain(parts)
+    }
+}
+
+pub fn format_duration(d: Duration) -> String {
+    format!("{:.3}ms", d.as_secs_f64() * 1000.0)
+}
+
+fn format_stats(stats: Option<Stats>, runs: usize) -> String {
+    match stats {
+        None => String::from("-"),
+        Some(s) if runs == 1 => format_duration(s.median),
+        Some(s) => format!(
+            "{} ({} - {})",
+            format_duration(s.median),
+            format_duration(s.min),
+            format_duration(s.max)
+        ),
+    }
+}
+
+/// A table of the median times, slowest day first.
+pub fn summary(benches: &[Bench]) -> String {
+    let mut benches: Vec<&Bench> = benches.iter().collect();
+    benches.sort_by_key(|b| std::cmp::Reverse(b.total()));
+
+    let rows: Vec<[String; 5]> = benches
+        .iter()
+        .map(|b| {
+            [
+                b.title.to_string(),
+                format_stats(Some(b.parse), b.runs),
+                format_stats(b.part(1), b.runs),
+                format_stats(b.part(2), b.runs),
+                format_duration(b.total()),
+            ]
+        })
+        .collect();
+
+    let header = [
+        String::from("day"),
+        String::from("parse"),
+        String::from("part 1"),
+        String::from("part 2"),
+        String::from("total"),
+    ];
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .map(|r| r[i].len())
+                .chain(Some(header[i].len()))
+                .max()
+                .unwrap()
+        })
+        .collect();
+
+    let mut table = String::new();
+    for row in Some(&header).into_iter().chain(&rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(i, (cell, width))| match i {
+                0 => format!("{:<1$}", cell, width),
+                _ => format!("{:>1$}", cell, width),
+            })
+            .collect();
+        writeln!(table, "{}", cells.join("  ").trim_end()).unwrap();
+    }
+    let total: Duration = benches.iter().map(|b| b.total()).sum();
+    writeln!(table, "total: {}", format_duration(total)).unwrap();
+    table
+}
+
+/// The times in nanoseconds as CSV, in day order so that reports can be diffed.
+pub fn report(benches: &[Bench]) -> String {
+    let mut report = String::from("day,step,runs,min_ns,median_ns,max_ns\n");
+    for bench in benches {
+        for (step, stats) in bench.steps() {
+            writeln!(
+                report,
+                "{},{},{},{},{},{}",
+                bench.day,
+                step,
+                bench.runs,
+                stats.min.as_nanos(),
+                stats.median.as_nanos(),
+                stats.max.as_nanos()
+            )
+            .unwrap();
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+fn bench_of(day: u32, parse: u64, parts: &[(u8, u64)]) -> Bench {
+    let stats = |ms: u64| Stats::new(vec![Duration::from_millis(ms)]);
+    Bench {
+        day,
+        title: aoc::day(day).unwrap().title(),
+        runs: 1,
+        parse: stats(parse),
+        parts: parts.iter().map(|(p, ms)| (*p, stats(*ms))).collect(),
+    }
+}
+
+#[test]
+fn test_stats() {
+    let ms = Duration::from_millis;
+    assert_eq!(
+        Stats::new(vec![ms(5), ms(1), ms(9), ms(3), ms(4)]),
+        Stats {
+            min: ms(1),
+            median: ms(4),
+            max: ms(9),
+        }
+    );
+}
+
+#[test]
+fn test_summary() {
+    let benches = vec![
+        bench_of(1, 1, &[(1, 2), (2, 3)]),
+        bench_of(15, 0, &[(1, 400), (2, 500)]),
+    ];
+    assert_eq!(
+        summary(&benches),
+        "\
+day                           parse     part 1     part 2      total
+Day 15: Dueling Generators  0.000ms  400.000ms  500.000ms  900.000ms
+Day 01: Inverse Captcha     1.000ms    2.000ms    3.000ms    6.000ms
+total: 906.000ms
+"
+    );
+}
+
+#[test]
+fn test_report() {
+    let benches = vec![bench_of(3, 1, &[(2, 2)])];
+    assert_eq!(
+        report(&benches),
+        "\
+day,step,runs,min_ns,median_ns,max_ns
+3,parse,1,1000000,1000000,1000000
+3,part2,1,2000000,2000000,2000000
+"
+    );
+}
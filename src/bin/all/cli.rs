@@ -0,0 +1,240 @@
+use crate::output::Format;
+use crate::pool;
+use aoc::DAYS;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "Usage: all [OPTIONS]
+
+Options:
+    -d, --day <DAYS>        days to run, e.g. `7`, `3-9` or `1,4,10-12` (default: all)
+    -p, --part <PART>       only run part 1 or part 2 (default: both)
+    -i, --input-dir <DIR>   directory of the inputNN.txt files (default: input)
+    -f, --file <FILE>       read the input of a single day from FILE, `-` for stdin
+        --stdin             read the input of a single day from stdin
+    -t, --time              print the time spent in every step, and a summary table
+    -r, --repeat <N>        run every day N times, and keep the min/median/max times
+        --report <FILE>     write the times to FILE, as CSV
+        --verify            check the answers against the expected ones, PASS/FAIL/MISSING
+        --answers <FILE>    the expected answers (default: answers.toml of the input directory)
+    -o, --format <FORMAT>   print the answers as json (lines), csv or markdown, with their times
+    -j, --jobs <N>          run the days and parts on N threads, 0 for one per CPU (default: 1)
+    -h, --help              print this message";
+
+#[derive(Debug, PartialEq)]
+pub enum Source {
+    Dir(PathBuf),
+    File(PathBuf),
+    Stdin,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub days: Vec<u32>,
+    pub parts: Vec<u8>,
+    pub source: Source,
+    pub time: bool,
+    pub repeat: usize,
+    pub report: Option<PathBuf>,
+    pub jobs: usize,
+    pub verify: bool,
+    pub answers: Option<PathBuf>,
+    pub format: Option<Format>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            days: DAYS.iter().map(|p| p.day()).collect(),
+            parts: vec![1, 2],
+            source: Source::Dir(PathBuf::from("input")),
+            time: false,
+            repeat: 1,
+            report: None,
+            jobs: 1,
+            verify: false,
+            answers: None,
+            format: None,
+        }
+    }
+}
+
+fn parse_day(s: &str) -> Result<u32, String> {
+    let day = s
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| format!("invalid day `{}`", s))?;
+    if aoc::day(day).is_none() {
+        return Err(format!("day {} is not implemented", day));
+    }
+    Ok(day)
+}
+
+fn parse_days(s: &str) -> Result<Vec<u32>, String> {
+    let mut days = vec![];
+    for range in s.split(',') {
+        let mut bounds = range.splitn(2, '-');
+        let start = parse_day(bounds.next().unwrap())?;
+        let end = match bounds.next() {
+            Some(end) => parse_day(end)?,
+            None => start,
+        };
+        if start > end {
+            return Err(format!("invalid range `{}`", range));
+        }
+        days.extend(start..=end);
+    }
+    days.sort();
+    days.dedup();
+    Ok(days)
+}
+
+fn parse_part(s: &str) -> Result<Vec<u8>, String> {
+    match s {
+        "1" => Ok(vec![1]),
+        "2" => Ok(vec![2]),
+        _ => Err(format!("invalid part `{}`, expected 1 or 2", s)),
+    }
+}
+
+fn parse_repeat(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("invalid number of runs `{}`", s)),
+    }
+}
+
+fn parse_jobs(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(0) => Ok(pool::available_threads()),
+        Ok(n) => Ok(n),
+        _ => Err(format!("invalid number of threads `{}`", s)),
+    }
+}
+
+impl Options {
+    /// The file of the expected answers, next to the inputs unless given.
+    pub fn answers_file(&self) -> PathBuf {
+        match (&self.answers, &self.source) {
+            (Some(file), _) => file.clone(),
+            (None, Source::Dir(dir)) => dir.join("answers.toml"),
+            (None, _) => PathBuf::from("input/answers.toml"),
+        }
+    }
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("missing value for `{}`", name))
+        };
+        match arg.as_str() {
+            "-d" | "--day" => options.days = parse_days(&value(&arg)?)?,
+            "-p" | "--part" => options.parts = parse_part(&value(&arg)?)?,
+            "-i" | "--input-dir" => options.source = Source::Dir(PathBuf::from(value(&arg)?)),
+            "-f" | "--file" => {
+                options.source = match value(&arg)?.as_str() {
+                    "-" => Source::Stdin,
+                    file => Source::File(PathBuf::from(file)),
+                }
+            }
+            "--stdin" => options.source = Source::Stdin,
+            "-t" | "--time" => options.time = true,
+            "-r" | "--repeat" => options.repeat = parse_repeat(&value(&arg)?)?,
+            "--report" => options.report = Some(PathBuf::from(value(&arg)?)),
+            "-j" | "--jobs" => options.jobs = parse_jobs(&value(&arg)?)?,
+            "--verify" => options.verify = true,
+            "-o" | "--format" => options.format = Some(Format::parse(&value(&arg)?)?),
+            "--answers" => options.answers = Some(PathBuf::from(value(&arg)?)),
+            _ => return Err(format!("unknown argument `{}`", arg)),
+        }
+    }
+
+    if options.verify && options.format.is_some() {
+        return Err(String::from(
+            "`--verify` prints text, it cannot be used with `--format`",
+        ));
+    }
+
+    if options.repeat > 1 && options.source == Source::Stdin {
+        return Err(String::from("stdin can only be read once, use `--file`"));
+    }
+
+    match options.source {
+        Source::Dir(_) => Ok(options),
+        _ if options.days.len() == 1 => Ok(options),
+        _ => Err(String::from(
+            "a single input can only be used with a single day, use `--day`",
+        )),
+    }
+}
+
+#[test]
+fn test_parse_days() {
+    assert_eq!(parse_days("7"), Ok(vec![7]));
+    assert_eq!(parse_days("3-5"), Ok(vec![3, 4, 5]));
+    assert_eq!(parse_days("10-12,1,4,11"), Ok(vec![1, 4, 10, 11, 12]));
+    assert!(parse_days("5-3").is_err());
+    assert!(parse_days("x").is_err());
+    assert!(parse_days("99").is_err());
+}
+
+#[test]
+fn test_parse_args() {
+    let args = |s: &str| s.split(' ').map(String::from).collect::<Vec<String>>();
+
+    assert_eq!(
+        parse_args(args("-d 7 -p 2 -i inputs")),
+        Ok(Options {
+            days: vec![7],
+            parts: vec![2],
+            source: Source::Dir(PathBuf::from("inputs")),
+            ..Options::default()
+        })
+    );
+    assert_eq!(
+        parse_args(args("--day 9 --file -")),
+        Ok(Options {
+            days: vec![9],
+            source: Source::Stdin,
+            ..Options::default()
+        })
+    );
+    assert_eq!(
+        parse_args(args("-t -r 5 --report times.csv")),
+        Ok(Options {
+            time: true,
+            repeat: 5,
+            report: Some(PathBuf::from("times.csv")),
+            ..Options::default()
+        })
+    );
+    assert_eq!(
+        parse_args(args("--verify --answers expected.toml")),
+        Ok(Options {
+            verify: true,
+            answers: Some(PathBuf::from("expected.toml")),
+            ..Options::default()
+        })
+    );
+    assert_eq!(
+        parse_args(args("-o csv")).map(|o| o.format),
+        Ok(Some(Format::Csv))
+    );
+    assert!(parse_args(args("--format xml")).is_err());
+    assert!(parse_args(args("--verify --format json")).is_err());
+    assert_eq!(parse_args(args("-j 3")).map(|o| o.jobs), Ok(3));
+    assert_eq!(
+        parse_args(args("--jobs 0")).map(|o| o.jobs),
+        Ok(pool::available_threads())
+    );
+    assert!(parse_args(args("-j x")).is_err());
+    assert!(parse_args(args("--stdin")).is_err());
+    assert!(parse_args(args("-d 1 --stdin -r 2")).is_err());
+    assert!(parse_args(args("-r 0")).is_err());
+    assert!(parse_args(args("-p 3")).is_err());
+    assert!(parse_args(args("--day")).is_err());
+}
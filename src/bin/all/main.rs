@@ -0,0 +1,228 @@
+mod answers;
+mod bench;
+mod cli;
+mod output;
+mod pool;
+
+use crate::answers::{Answers, Verdict};
+use crate::bench::Bench;
+use crate::cli::{Options, Source, USAGE};
+use aoc::{Answer, ParseError, Puzzle, Run};
+use std::io::Read;
+use std::process;
+
+fn read_input(source: &Source, day: u32) -> Result<String, String> {
+    match source {
+        Source::Dir(dir) => {
+            let filename = dir.join(format!("input{:02}.txt", day));
+            std::fs::read_to_string(&filename).map_err(|e| format!("{}: {}", filename.display(), e))
+        }
+        Source::File(filename) => {
+            std::fs::read_to_string(filename).map_err(|e| format!("{}: {}", filename.display(), e))
+        }
+        Source::Stdin => {
+            let mut input = String::new();
+            std::io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|e| format!("stdin: {}", e))?;
+            Ok(input)
+        }
+    }
+}
+
+// some parts of a day, run as many times as asked
+struct Job<'a> {
+    puzzle: &'static dyn Puzzle,
+    input: &'a Result<String, String>,
+    parts: Vec<u8>,
+}
+
+enum Outcome {
+    Skipped(String),
+    Failed(ParseError),
+    Done(Vec<Run>),
+}
+
+fn run_job(job: &Job, repeat: usize) -> Outcome {
+    let input = match job.input {
+        Ok(input) => input,
+        Err(e) => return Outcome::Skipped(e.clone()),
+    };
+    match (0..repeat)
+        .map(|_| job.puzzle.run(input, &job.parts))
+        .collect()
+    {
+        Ok(runs) => Outcome::Done(runs),
+        Err(e) => Outcome::Failed(e),
+    }
+}
+
+// put back together the runs of the parts of a day that were run separately
+fn merge_runs(outcomes: Vec<Outcome>) -> Outcome {
+    let mut merged: Option<Vec<Run>> = None;
+    for outcome in outcomes {
+        let runs = match outcome {
+            Outcome::Done(runs) => runs,
+            failed => return failed,
+        };
+        merged = Some(match merged {
+            None => runs,
+            Some(merged) => merged
+                .into_iter()
+                .zip(runs)
+                .map(|(mut merged, run)| {
+                    merged.solutions.extend(run.solutions);
+                    merged
+                })
+                .collect(),
+        });
+    }
+    Outcome::Done(merged.unwrap_or_default())
+}
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", USAGE);
+        return;
+    }
+
+    let options: Options = match cli::parse_args(args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let answers = if options.verify {
+        let file = options.answers_file();
+        match std::fs::read_to_string(&file).map_err(|e| e.to_string()) {
+            Ok(s) => Answers::parse(&s),
+            Err(e) => Err(e),
+        }
+        .unwrap_or_else(|e| {
+            eprintln!("error: {}: {}", file.display(), e);
+            process::exit(2);
+        })
+    } else {
+        Answers::default()
+    };
+
+    let inputs: Vec<(&'static dyn Puzzle, Result<String, String>)> = options
+        .days
+        .iter()
+        .map(|day| (aoc::day(*day).unwrap(), read_input(&options.source, *day)))
+        .collect();
+
+    // when running in parallel, the parts of a day can overlap too
+    let jobs: Vec<Job> = inputs
+        .iter()
+        .flat_map(|(puzzle, input)| {
+            let parts = match (input, options.jobs) {
+                (Ok(_), jobs) if jobs > 1 => options.parts.iter().map(|p| vec![*p]).collect(),
+                _ => vec![options.parts.clone()],
+            };
+            parts.into_iter().map(move |parts| Job {
+                puzzle: *puzzle,
+                input,
+                parts,
+            })
+        })
+        .collect();
+
+    // with a machine-readable format, only the answers go to stdout
+    let notice = |message: String| match options.format {
+        Some(_) => eprintln!("{}", message),
+        None => println!("{}", message),
+    };
+    if let Some(header) = options.format.and_then(|f| f.header()) {
+        println!("{}", header);
+    }
+
+    let mut failed = false;
+    let mut benches = vec![];
+    let mut outcomes = vec![];
+    pool::run_ordered(
+        &jobs,
+        options.jobs,
+        |job| run_job(job, options.repeat),
+        |i, outcome| {
+            outcomes.push(outcome);
+            let puzzle = jobs[i].puzzle;
+            if jobs.get(i + 1).map(|j| j.puzzle.day()) == Some(puzzle.day()) {
+                return;
+            }
+
+            // all the parts of the day are done
+            match merge_runs(std::mem::take(&mut outcomes)) {
+                // the expected answers cannot be checked without an input
+                Outcome::Skipped(e)
+                    if options.verify
+                        && options
+                            .parts
+                            .iter()
+                            .any(|p| answers.expects(puzzle.day(), *p)) =>
+                {
+                    notice(format!("{}: MISSING ({})", puzzle.title(), e));
+                    failed = true;
+                }
+                Outcome::Skipped(e) => notice(format!("{}: skipped ({})", puzzle.title(), e)),
+                Outcome::Failed(e) => {
+                    notice(format!("{}: error: {}", puzzle.title(), e));
+                    failed = true;
+                }
+                Outcome::Done(runs) => {
+                    let bench = Bench::new(puzzle, &runs);
+                    for solution in &runs[0].solutions {
+                        let median = bench.part(solution.part).unwrap().median;
+                        if let Some(format) = options.format {
+                            println!("{}", format.row(puzzle, solution, median));
+                            continue;
+                        }
+
+                        let answer = solution.answer.to_string();
+                        let mut line = format!("{} ({}/2): ", puzzle.title(), solution.part);
+                        if options.verify {
+                            match answers.check(puzzle.day(), solution.part, &answer) {
+                                // nothing to check when there is no answer
+                                Verdict::Missing if solution.answer == Answer::None => {
+                                    line += &answer
+                                }
+                                Verdict::Pass => line += "PASS",
+                                Verdict::Fail(expected) => {
+                                    line +=
+                                        &format!("FAIL (expected {}, got {})", expected, answer);
+                                    failed = true;
+                                }
+                                Verdict::Missing => line += &format!("MISSING (got {})", answer),
+                            }
+                        } else {
+                            line += &answer;
+                        }
+                        if options.time {
+                            line += &format!(" [{}]", bench::format_duration(median));
+                        }
+                        println!("{}", line);
+                    }
+                    benches.push(bench);
+                }
+            }
+        },
+    );
+
+    if options.time {
+        notice(format!("\n{}", bench::summary(&benches).trim_end()));
+    }
+
+    if let Some(report) = &options.report {
+        if let Err(e) = std::fs::write(report, bench::report(&benches)) {
+            eprintln!("error: {}: {}", report.display(), e);
+            failed = true;
+        }
+    }
+
+    if failed {
+        process::exit(1);
+    }
+}
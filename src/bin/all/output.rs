@@ -0,0 +1,128 @@
+use aoc::{Answer, Puzzle, Solution};
+use std::time::Duration;
+
+/// The machine-readable formats of the answers, one row per part.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Json,
+    Csv,
+    Markdown,
+}
+
+impl Format {
+    pub fn parse(s: &str) -> Result<Format, String> {
+        match s {
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "markdown" | "md" => Ok(Format::Markdown),
+            _ => Err(format!(
+                "invalid format `{}`, expected json, csv or markdown",
+                s
+            )),
+        }
+    }
+
+    /// What comes before the first row, if anything.
+    pub fn header(self) -> Option<&'static str> {
+        match self {
+            Format::Json => None,
+            Format::Csv => Some("day,title,part,answer,duration_ns"),
+            Format::Markdown => Some(
+                "| day | title | part | answer | duration |\n\
+                 |----:|-------|-----:|--------|---------:|",
+            ),
+        }
+    }
+
+    pub fn row(self, puzzle: &dyn Puzzle, solution: &Solution, duration: Duration) -> String {
+        let (day, title, part) = (puzzle.day(), puzzle.title(), solution.part);
+        match self {
+            Format::Json => {
+                let answer = match &solution.answer {
+                    Answer::Number(n) => n.to_string(),
+                    Answer::Text(s) => json_string(s),
+                    Answer::None => String::from("null"),
+                };
+                format!(
+                    r#"{{"day":{},"title":{},"part":{},"answer":{},"duration_ns":{}}}"#,
+                    day,
+                    json_string(title),
+                    part,
+                    answer,
+                    duration.as_nanos()
+                )
+            }
+            Format::Csv => format!(
+                "{},{},{},{},{}",
+                day,
+                csv_field(title),
+                part,
+                csv_field(&solution.answer.to_string()),
+                duration.as_nanos()
+            ),
+            Format::Markdown => format!(
+                "| {} | {} | {} | {} | {} |",
+                day,
+                title.replace('|', "\\|"),
+                part,
+                solution.answer.to_string().replace('|', "\\|"),
+                crate::bench::format_duration(duration)
+            ),
+        }
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[test]
+fn test_row() {
+    let puzzle = aoc::day(5).unwrap();
+    let solution = Solution {
+        part: 2,
+        answer: Answer::Number(26),
+        duration: Duration::from_micros(1500),
+    };
+    let row = |format: Format| format.row(puzzle, &solution, solution.duration);
+
+    assert_eq!(
+        row(Format::Json),
+        r#"{"day":5,"title":"Day 05: A Maze of Twisty Trampolines, All Alike","part":2,"answer":26,"duration_ns":1500000}"#
+    );
+    assert_eq!(
+        row(Format::Csv),
+        r#"5,"Day 05: A Maze of Twisty Trampolines, All Alike",2,26,1500000"#
+    );
+    assert_eq!(
+        row(Format::Markdown),
+        "| 5 | Day 05: A Maze of Twisty Trampolines, All Alike | 2 | 26 | 1.500ms |"
+    );
+
+    let solution = Solution {
+        answer: Answer::Text(String::from("a\"b")),
+        ..solution
+    };
+    assert!(Format::Json
+        .row(puzzle, &solution, solution.duration)
+        .contains(r#""answer":"a\"b""#));
+}
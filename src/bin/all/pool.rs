@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// Apply `f` to every job on `threads` threads, and hand the results to `done`
+/// in the order of the jobs, as soon as all the previous ones are finished.
+pub fn run_ordered<T, R, F, D>(jobs: &[T], threads: usize, f: F, mut done: D)
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+    D: FnMut(usize, R),
+{
+    if threads <= 1 {
+        jobs.iter().enumerate().for_each(|(i, job)| done(i, f(job)));
+        return;
+    }
+
+    let next_job = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..threads.min(jobs.len()) {
+            let sender = sender.clone();
+            let (next_job, f) = (&next_job, &f);
+            scope.spawn(move || loop {
+                let i = next_job.fetch_add(1, Ordering::SeqCst);
+                match jobs.get(i) {
+                    Some(job) => sender.send((i, f(job))).unwrap(),
+                    None => break,
+                }
+            });
+        }
+        drop(sender);
+
+        // the results arrive in any order, keep them until their turn
+        let mut pending = HashMap::new();
+        let mut next_result = 0;
+        for (i, result) in receiver {
+            pending.insert(i, result);
+            while let Some(result) = pending.remove(&next_result) {
+                done(next_result, result);
+                next_result += 1;
+            }
+        }
+    });
+}
+
+/// One thread per CPU.
+pub fn available_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+#[test]
+fn test_run_ordered() {
+    let jobs: Vec<u64> = (0..20).collect();
+    for threads in &[1, 4] {
+        let mut results = vec![];
+        run_ordered(
+            &jobs,
+            *threads,
+            |n| {
+                // make the first jobs the slowest ones
+                thread::sleep(std::time::Duration::from_millis(20 - n));
+                n * n
+            },
+            |i, r| results.push((i, r)),
+        );
+        assert_eq!(
+            results,
+            jobs.iter()
+                .map(|n| (*n as usize, n * n))
+                .collect::<Vec<_>>()
+        );
+    }
+}
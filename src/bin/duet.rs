@@ -0,0 +1,214 @@
+use aoc::day18::{self, Day18};
+use aoc::duet::{Breakpoint, Debugger, Event, Semantics};
+use aoc::Solver;
+use std::io::{self, BufRead, Write};
+use std::process;
+
+const USAGE: &str = "Usage: duet [--sound] FILE
+
+Step through the day 18 program in FILE, run by two programs sending values to each other,
+or by a single one playing sounds with `--sound`.";
+
+const HELP: &str = "Commands:
+    s, step [N]             run N instructions (default: 1)
+    c, continue             run until a breakpoint, a deadlock or the end of the programs
+    b, break PC             stop before running the instruction at PC
+    b, break REG OP VALUE   stop when the condition becomes true, e.g. `break a > 5`
+    d, delete N             remove the breakpoint N
+    breakpoints             list the breakpoints
+    r, regs                 print the registers of every program
+    i, info                 print the state and the queue of every program
+    l, list                 print the instructions around the next one of every program
+    h, help                 print this message
+    q, quit                 exit";
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Step(usize),
+    Continue,
+    Break(Breakpoint),
+    Delete(usize),
+    Breakpoints,
+    Registers,
+    Info,
+    List,
+    Help,
+    Quit,
+}
+
+fn parse_number<T: std::str::FromStr>(s: &str) -> Result<T, String> {
+    s.parse().map_err(|_| format!("invalid number `{}`", s))
+}
+
+fn parse_breakpoint(args: &[&str]) -> Result<Breakpoint, String> {
+    match args {
+        [pc] => Ok(Breakpoint::Pc(parse_number(pc)?)),
+        [name, cmp, value] => {
+            let name = match name.as_bytes() {
+                [c @ b'a'..=b'z'] => *c as char,
+                _ => return Err(format!("invalid register `{}`", name)),
+            };
+            let cmp = cmp
+                .parse()
+                .map_err(|_| format!("invalid comparison `{}`", cmp))?;
+            Ok(Breakpoint::Register(name, cmp, parse_number(value)?))
+        }
+        _ => Err(String::from("expected `break PC` or `break REG OP VALUE`")),
+    }
+}
+
+fn parse_command(line: &str) -> Result<Option<Command>, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let command = match words.as_slice() {
+        [] => return Ok(None),
+        ["s"] | ["step"] => Command::Step(1),
+        ["s", n] | ["step", n] => Command::Step(parse_number(n)?),
+        ["c"] | ["continue"] => Command::Continue,
+        ["b", args @ ..] | ["break", args @ ..] => Command::Break(parse_breakpoint(args)?),
+        ["d", n] | ["delete", n] => Command::Delete(parse_number(n)?),
+        ["breakpoints"] => Command::Breakpoints,
+        ["r"] | ["regs"] => Command::Registers,
+        ["i"] | ["info"] => Command::Info,
+        ["l"] | ["list"] => Command::List,
+        ["h"] | ["help"] => Command::Help,
+        ["q"] | ["quit"] => Command::Quit,
+        _ => return Err(format!("unknown command `{}`, try `help`", line.trim())),
+    };
+    Ok(Some(command))
+}
+
+fn describe(debugger: &Debugger, event: Event) -> String {
+    match event {
+        Event::Stepped(id) => match debugger.instruction(id) {
+            Some(instruction) => format!(
+                "program {} at {}: {}",
+                id,
+                debugger.program(id).pc(),
+                instruction
+            ),
+            None => format!("program {} terminated", id),
+        },
+        Event::Blocked(id) => format!(
+            "program {} is blocked at {}, waiting for a value",
+            id,
+            debugger.program(id).pc()
+        ),
+        Event::Terminated(id) => format!("program {} terminated", id),
+        Event::Breakpoint(id, index) => format!(
+            "program {} hit breakpoint {} ({}) at {}",
+            id,
+            index,
+            debugger.breakpoints()[index],
+            debugger.program(id).pc()
+        ),
+        Event::Deadlock => format!("deadlock, every program is blocked\n{}", debugger),
+        Event::Finished => format!("every program is terminated\n{}", debugger),
+    }
+}
+
+// run the command, false to quit
+fn execute(debugger: &mut Debugger, command: Command) -> bool {
+    match command {
+        Command::Step(n) => {
+            for _ in 0..n {
+                let event = debugger.step();
+                println!("{}", describe(debugger, event));
+                if let Event::Deadlock | Event::Finished = event {
+                    break;
+                }
+            }
+        }
+        Command::Continue => {
+            let event = debugger.run();
+            println!("{}", describe(debugger, event));
+        }
+        Command::Break(breakpoint) => {
+            let index = debugger.add_breakpoint(breakpoint);
+            println!("breakpoint {}: {}", index, breakpoint);
+        }
+        Command::Delete(index) => match debugger.remove_breakpoint(index) {
+            Some(breakpoint) => println!("deleted breakpoint {}: {}", index, breakpoint),
+            None => println!("there is no breakpoint {}", index),
+        },
+        Command::Breakpoints => {
+            for (index, breakpoint) in debugger.breakpoints().iter().enumerate() {
+                println!("{}: {}", index, breakpoint);
+            }
+        }
+        Command::Registers => {
+            for id in 0..debugger.count() {
+                println!("program {}: {}", id, debugger.registers(id));
+            }
+        }
+        Command::Info => print!("{}", debugger),
+        Command::List => {
+            for id in 0..debugger.count() {
+                println!("program {}:\n{}", id, debugger.listing(id, 3));
+            }
+        }
+        Command::Help => println!("{}", HELP),
+        Command::Quit => return false,
+    }
+    true
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (semantics, file) = match args.as_slice() {
+        [flag, file] if flag == "--sound" => (Semantics::Sound, file),
+        [file] if file != "-h" && file != "--help" => (Semantics::Queues, file),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    let instructions = std::fs::read_to_string(file)
+        .map_err(|e| format!("{}: {}", file, e))
+        .and_then(|input| Day18::parse_input(&input).map_err(|e| e.to_string()))
+        .unwrap_or_else(|e| {
+            eprintln!("error: {}", e);
+            process::exit(2);
+        });
+    let bytecode = day18::compile(&instructions);
+    let mut debugger = Debugger::new(&instructions, &bytecode, semantics);
+
+    print!("{}(duet) ", debugger);
+    io::stdout().flush().unwrap();
+    for line in io::stdin().lock().lines() {
+        let line = line.unwrap();
+        match parse_command(&line) {
+            Ok(Some(command)) => {
+                if !execute(&mut debugger, command) {
+                    return;
+                }
+            }
+            Ok(None) => {}
+            Err(e) => println!("error: {}", e),
+        }
+        print!("(duet) ");
+        io::stdout().flush().unwrap();
+    }
+    println!();
+}
+
+#[test]
+fn test_parse_command() {
+    use aoc::vm::Cmp;
+
+    assert_eq!(parse_command("  "), Ok(None));
+    assert_eq!(parse_command("s"), Ok(Some(Command::Step(1))));
+    assert_eq!(parse_command("step 10"), Ok(Some(Command::Step(10))));
+    assert_eq!(
+        parse_command("b 4"),
+        Ok(Some(Command::Break(Breakpoint::Pc(4))))
+    );
+    assert_eq!(
+        parse_command("break a >= -2"),
+        Ok(Some(Command::Break(Breakpoint::Register('a', Cmp::Ge, -2))))
+    );
+    assert!(parse_command("break A > 1").is_err());
+    assert!(parse_command("break a => 1").is_err());
+    assert!(parse_command("step x").is_err());
+    assert!(parse_command("jump").is_err());
+}
@@ -0,0 +1,64 @@
+use aoc::day08::{Day08, ExecuteError, Interpreter};
+use aoc::{ParseError, Solver};
+use std::io::{self, BufRead, Write};
+use std::process;
+
+const USAGE: &str = "Usage: registers [FILE]
+
+Run the instructions of the day 08 register language, one per line, and print the registers
+after each of them. The instructions of FILE are run first, then the ones typed in.
+
+    a set 5
+    b inc a if a > 1 and c == 0
+    a mul -2 if b < 0 or a >= 5
+
+Operations: inc, dec, set, mul, mod. Comparisons: ==, !=, >, <, >=, <=.
+Lines starting with `#` are comments.";
+
+// run a line, and print the registers
+fn execute(interpreter: &mut Interpreter, line_number: usize, line: &str) -> Result<(), String> {
+    if line.trim().is_empty() || line.trim_start().starts_with('#') {
+        return Ok(());
+    }
+    interpreter.execute(line).map_err(|e| match e {
+        ExecuteError::Parse(e) => ParseError::new(Day08::DAY, line_number, line, e).to_string(),
+        ExecuteError::Fault(fault) => format!("line {}: {}", line_number, fault),
+    })?;
+    println!("{}", interpreter);
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut interpreter = Interpreter::new();
+    match args.as_slice() {
+        [] => {}
+        [file] if file != "-h" && file != "--help" => {
+            let input = std::fs::read_to_string(file).unwrap_or_else(|e| {
+                eprintln!("error: {}: {}", file, e);
+                process::exit(2);
+            });
+            for (n, line) in input.lines().enumerate() {
+                if let Err(e) = execute(&mut interpreter, n + 1, line) {
+                    eprintln!("error: {}: {}", file, e);
+                    process::exit(2);
+                }
+            }
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    }
+
+    print!("> ");
+    io::stdout().flush().unwrap();
+    for (n, line) in io::stdin().lock().lines().enumerate() {
+        if let Err(e) = execute(&mut interpreter, n + 1, &line.unwrap()) {
+            println!("error: {}", e);
+        }
+        print!("> ");
+        io::stdout().flush().unwrap();
+    }
+    println!();
+}
@@ -1,3 +1,7 @@
+use crate::error::{LineError, ParseError};
+use nom::types::CompleteStr;
+use nom::{Context, Err, IResult};
+
 #[macro_export]
 macro_rules! vec_of_strings {
     // match a list of expressions separated by comma:
@@ -11,3 +15,169 @@ macro_rules! vec_of_strings {
 pub fn to_char(s: &str) -> char {
     s.as_bytes()[0] as char
 }
+
+/// Byte offset of a token taken from the line, e.g. with `split`.
+pub fn offset(line: &str, token: &str) -> usize {
+    token.as_ptr() as usize - line.as_ptr() as usize
+}
+
+/// Split a line on whitespaces, keeping the byte offset of every word.
+pub fn words(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split(char::is_whitespace)
+        .filter(|w| !w.is_empty())
+        .map(move |w| (offset(line, w), w))
+}
+
+/// Parse every non-empty line of an input.
+pub fn parse_lines<T, F>(day: u32, input: &str, parse: F) -> Result<Vec<T>, ParseError>
+where
+    F: Fn(&str) -> Result<T, LineError>,
+{
+    input
+        .split('\n')
+        .enumerate()
+        .filter(|(_, l)| !l.is_empty())
+        .map(|(n, l)| parse(l).map_err(|e| ParseError::new(day, n + 1, l, e)))
+        .collect()
+}
+
+/// An error for something the input lacks as a whole, pointing at its first line.
+pub fn missing(day: u32, input: &str, expected: &'static str) -> ParseError {
+    let (n, line) = input
+        .split('\n')
+        .enumerate()
+        .find(|(_, l)| !l.is_empty())
+        .unwrap_or((0, ""));
+    ParseError::new(day, n + 1, line, LineError::new(0, expected))
+}
+
+/// Parse an input made of a single line, ignoring the trailing whitespaces.
+pub fn parse_line<T, F>(day: u32, input: &str, parse: F) -> Result<T, ParseError>
+where
+    F: Fn(&str) -> Result<T, LineError>,
+{
+    let input = input.trim_end();
+    if let Some(end) = input.find('\n') {
+        let extra = &input[end + 1..];
+        return Err(ParseError::new(
+            day,
+            2,
+            extra,
+            LineError::new(0, "a single line"),
+        ));
+    }
+    parse(input).map_err(|e| ParseError::new(day, 1, input, e))
+}
+
+/// Parse an input whose first line can set a parameter, e.g. `size: 5`, for the days whose
+/// examples are smaller than the puzzle. The rest of the input goes to `parse_rest`, its
+/// errors being moved down a line when the parameter is there.
+pub fn parse_with_parameter<'a, T, R, V, P>(
+    day: u32,
+    input: &'a str,
+    name: &str,
+    parse_value: V,
+    expected: &'static str,
+    parse_rest: P,
+) -> Result<R, ParseError>
+where
+    V: Fn(&str) -> Option<T>,
+    P: FnOnce(Option<T>, &'a str) -> Result<R, ParseError>,
+{
+    let (first, rest) = input.split_once('\n').unwrap_or((input, ""));
+    let value = match first.strip_prefix(name).and_then(|l| l.strip_prefix(": ")) {
+        Some(value) => value,
+        None => return parse_rest(None, input),
+    };
+    let value = parse_value(value.trim_end())
+        .ok_or_else(|| ParseError::new(day, 1, first, LineError::new(name.len() + 2, expected)))?;
+    parse_rest(Some(value), rest).map_err(|e| ParseError {
+        line: e.line + 1,
+        ..e
+    })
+}
+
+/// Run a nom parser over a whole line.
+pub fn parse_nom<'a, T, P>(line: &'a str, parser: P, expected: &'static str) -> Result<T, LineError>
+where
+    P: Fn(CompleteStr<'a>) -> IResult<CompleteStr<'a>, T>,
+{
+    match parser(CompleteStr(line)) {
+        Ok((CompleteStr(""), value)) => Ok(value),
+        Ok((CompleteStr(rest), _)) => Err(LineError::new(line.len() - rest.len(), "end of line")),
+        Err(Err::Error(Context::Code(CompleteStr(rest), _)))
+        | Err(Err::Failure(Context::Code(CompleteStr(rest), _))) => {
+            Err(LineError::new(line.len() - rest.len(), expected))
+        }
+        Err(Err::Incomplete(_)) => Err(LineError::new(line.len(), expected)),
+    }
+}
+
+/// Run a nom parser over a whole input of several lines, ignoring the trailing whitespaces.
+pub fn parse_nom_input<'a, T, P>(
+    day: u32,
+    input: &'a str,
+    parser: P,
+    expected: &'static str,
+) -> Result<T, ParseError>
+where
+    P: Fn(CompleteStr<'a>) -> IResult<CompleteStr<'a>, T>,
+{
+    let (rest, expected) = match parser(CompleteStr(input)) {
+        Ok((CompleteStr(rest), value)) if rest.trim().is_empty() => return Ok(value),
+        Ok((CompleteStr(rest), _)) => (rest.trim_start(), expected),
+        Err(Err::Error(Context::Code(CompleteStr(rest), _)))
+        | Err(Err::Failure(Context::Code(CompleteStr(rest), _))) => (rest, expected),
+        Err(Err::Incomplete(_)) => ("", expected),
+    };
+
+    let offset = input.len() - rest.len();
+    let start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let end = input[offset..]
+        .find('\n')
+        .map_or(input.len(), |i| offset + i);
+    Err(ParseError::new(
+        day,
+        input[..offset].matches('\n').count() + 1,
+        &input[start..end],
+        LineError::new(offset - start, expected),
+    ))
+}
+
+#[test]
+fn test_words() {
+    assert_eq!(
+        words(" 5  1\t9 ").collect::<Vec<_>>(),
+        vec![(1, "5"), (4, "1"), (6, "9")]
+    );
+}
+
+#[test]
+fn test_parse_lines() {
+    let parse = |l: &str| l.parse::<u32>().map_err(|_| LineError::new(0, "a number"));
+
+    assert_eq!(parse_lines(1, "\n1\n2\n", parse), Ok(vec![1, 2]));
+    assert_eq!(
+        parse_lines(1, "\n1\nx\n", parse).unwrap_err().to_string(),
+        "day 01, line 3, column 1: expected a number, found `x`"
+    );
+}
+
+#[test]
+fn test_parse_with_parameter() {
+    let parse = |input| {
+        let value = |v: &str| v.parse::<u32>().ok();
+        parse_with_parameter(1, input, "size", value, "a number", |size, rest| {
+            let line = parse_line(1, rest, |l| Ok(l.to_string()))?;
+            Ok((size, line))
+        })
+    };
+
+    assert_eq!(parse("abc"), Ok((None, String::from("abc"))));
+    assert_eq!(parse("size: 5\nabc"), Ok((Some(5), String::from("abc"))));
+    assert_eq!(
+        parse("size: x\nabc").unwrap_err().to_string(),
+        "day 01, line 1, column 7: expected a number, found `x`"
+    );
+    assert_eq!(parse("size: 5\nabc\ndef").unwrap_err().line, 3);
+}
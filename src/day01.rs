@@ -1,50 +1,64 @@
-pub fn title() -> &'static str {
-    "Day 01: Inverse Captcha"
-}
+use crate::common;
+use crate::error::LineError;
+use crate::{ParseError, Solver};
+
+pub struct Day01;
+
+impl Solver for Day01 {
+    const DAY: u32 = 1;
+    const TITLE: &'static str = "Day 01: Inverse Captcha";
+
+    type Input = Vec<u32>;
+    type Output1 = u32;
+    type Output2 = u32;
 
-pub fn answer1(input: &str) -> u32 {
-    let digits: Vec<u32> = input
-        .chars()
-        .map(|c| c.to_digit(10))
-        .filter(Option::is_some)
-        .map(Option::unwrap) // safe because of the .is_some()
-        .collect();
-
-    let digits_cycled_iter = digits.iter().cycle().skip(1);
-    digits
-        .iter()
-        .zip(digits_cycled_iter)
-        .fold(0, |sum, (a, b)| if a == b { sum + a } else { sum })
+    fn parse_input(input: &str) -> Result<Vec<u32>, ParseError> {
+        common::parse_line(Self::DAY, input, |line| {
+            line.char_indices()
+                .map(|(i, c)| c.to_digit(10).ok_or_else(|| LineError::new(i, "a digit")))
+                .collect()
+        })
+    }
+
+    fn answer1(digits: &Vec<u32>) -> u32 {
+        let digits_cycled_iter = digits.iter().cycle().skip(1);
+        digits
+            .iter()
+            .zip(digits_cycled_iter)
+            .fold(0, |sum, (a, b)| if a == b { sum + a } else { sum })
+    }
+
+    fn answer2(digits: &Vec<u32>) -> u32 {
+        let digits_cycled_iter = digits.iter().cycle().skip(digits.len() / 2);
+        digits
+            .iter()
+            .zip(digits_cycled_iter)
+            .fold(0, |sum, (a, b)| if a == b { sum + a } else { sum })
+    }
 }
 
-pub fn answer2(input: &str) -> u32 {
-    let digits: Vec<u32> = input
-        .chars()
-        .map(|c| c.to_digit(10))
-        .filter(Option::is_some)
-        .map(Option::unwrap) // safe because of the .is_some()
-        .collect();
-
-    let digits_cycled_iter = digits.iter().cycle().skip(digits.len() / 2);
-    digits
-        .iter()
-        .zip(digits_cycled_iter)
-        .fold(0, |sum, (a, b)| if a == b { sum + a } else { sum })
+#[test]
+fn test_parse_input() {
+    assert_eq!(Day01::parse_input("1122\n").unwrap(), vec![1, 1, 2, 2]);
+    assert_eq!(
+        Day01::parse_input("11x2").unwrap_err().to_string(),
+        "day 01, line 1, column 3: expected a digit, found `x2`"
+    );
 }
 
 #[test]
 fn test_answer1() {
-    assert_eq!(answer1("1122"), 3);
-    assert_eq!(answer1("1111"), 4);
-    assert_eq!(answer1("1234"), 0);
-    assert_eq!(answer1("91212129"), 9);
+    assert_eq!(Day01::run1("1122").unwrap(), 3);
+    assert_eq!(Day01::run1("1111").unwrap(), 4);
+    assert_eq!(Day01::run1("1234").unwrap(), 0);
+    assert_eq!(Day01::run1("91212129").unwrap(), 9);
 }
 
 #[test]
 fn test_answer2() {
-    assert_eq!(answer2("1212"), 6);
-    assert_eq!(answer2("1221"), 0);
-    assert_eq!(answer2("123425"), 4);
-    assert_eq!(answer2("123123"), 12);
-    assert_eq!(answer2("12131415"), 4);
+    assert_eq!(Day01::run2("1212").unwrap(), 6);
+    assert_eq!(Day01::run2("1221").unwrap(), 0);
+    assert_eq!(Day01::run2("123425").unwrap(), 4);
+    assert_eq!(Day01::run2("123123").unwrap(), 12);
+    assert_eq!(Day01::run2("12131415").unwrap(), 4);
 }
@@ -1,36 +1,41 @@
-pub fn title() -> &'static str {
-    "Day 02: Corruption Checksum"
-}
+use crate::common;
+use crate::error::LineError;
+use crate::{ParseError, Solver};
 
-pub fn answer1(input: &str) -> u32 {
-    let spreadsheet = parse_input(input);
+pub struct Day02;
 
-    spreadsheet.iter().fold(0, |checksum, line| {
-        checksum + (line.iter().max().unwrap() - line.iter().min().unwrap())
-    })
-}
+impl Solver for Day02 {
+    const DAY: u32 = 2;
+    const TITLE: &'static str = "Day 02: Corruption Checksum";
 
-pub fn answer2(input: &str) -> u32 {
-    let spreadsheet = parse_input(input);
+    type Input = Vec<Vec<u32>>;
+    type Output1 = u32;
+    type Output2 = u32;
 
-    spreadsheet.iter().fold(0, |checksum, line| {
-        let divisible = evenly_divisible(line);
-        checksum + (divisible.0 / divisible.1)
-    })
-}
-
-fn parse_input(input: &str) -> Vec<Vec<u32>> {
-    let lines = input.split('\n');
-    let spreadsheet: Vec<Vec<u32>> = lines
-        .filter(|l| *l != "")
-        .map(|l| {
-            l.split(char::is_whitespace)
-                .map(|number| number.parse().unwrap())
+    fn parse_input(input: &str) -> Result<Vec<Vec<u32>>, ParseError> {
+        common::parse_lines(Self::DAY, input, |l| {
+            common::words(l)
+                .map(|(offset, number)| {
+                    number
+                        .parse()
+                        .map_err(|_| LineError::new(offset, "a number"))
+                })
                 .collect()
         })
-        .collect();
+    }
+
+    fn answer1(spreadsheet: &Vec<Vec<u32>>) -> u32 {
+        spreadsheet.iter().fold(0, |checksum, line| {
+            checksum + (line.iter().max().unwrap() - line.iter().min().unwrap())
+        })
+    }
 
-    spreadsheet
+    fn answer2(spreadsheet: &Vec<Vec<u32>>) -> u32 {
+        spreadsheet.iter().fold(0, |checksum, line| {
+            let divisible = evenly_divisible(line);
+            checksum + (divisible.0 / divisible.1)
+        })
+    }
 }
 
 fn evenly_divisible(line: &[u32]) -> (u32, u32) {
@@ -53,7 +58,7 @@ fn test_answer1() {
 2 4 6 8"#,
     );
 
-    assert_eq!(answer1(&input), 18);
+    assert_eq!(Day02::run1(&input).unwrap(), 18);
 }
 
 #[test]
@@ -65,7 +70,7 @@ fn test_answer2() {
 3 8 6 5"#,
     );
 
-    assert_eq!(answer2(&input), 9);
+    assert_eq!(Day02::run2(&input).unwrap(), 9);
 }
 
 #[test]
@@ -1,8 +1,9 @@
+use crate::common;
+use crate::error::LineError;
+use crate::{ParseError, Solver};
 use std::fmt;
 
-pub fn title() -> &'static str {
-    "Day 03: Spiral Memory"
-}
+pub struct Day03;
 
 #[derive(Debug)]
 struct Spiral {
@@ -106,33 +107,52 @@ impl fmt::Display for Spiral {
     }
 }
 
-pub fn answer1(input: isize) -> u32 {
-    let spiral = Spiral::new(input);
+impl Solver for Day03 {
+    const DAY: u32 = 3;
+    const TITLE: &'static str = "Day 03: Spiral Memory";
 
-    (spiral.dx.abs() + spiral.dy.abs()) as u32
-}
+    type Input = isize;
+    type Output1 = u32;
+    type Output2 = u32;
 
-pub fn answer2(input: isize) -> u32 {
-    // first pass to compute the required size for the grid here
-    let spiral = Spiral::new(input);
-    let required_grid_size = spiral.size;
+    fn parse_input(input: &str) -> Result<isize, ParseError> {
+        common::parse_line(Self::DAY, input, |line| match line.parse() {
+            Ok(square) if square >= 1 => Ok(square),
+            _ => Err(LineError::new(0, "a square from 1")),
+        })
+    }
 
-    // second pass with the correct size
-    let mut spiral = Spiral::default();
-    spiral.size = required_grid_size;
-    spiral.grid = vec![0; (required_grid_size * required_grid_size) as usize];
-
-    let center = (required_grid_size / 2, required_grid_size / 2);
-    spiral.set(center, 1);
-
-    loop {
-        spiral.next();
-        let center_d = (center.0 + spiral.dx, center.1 + spiral.dy);
-        let sum = spiral.neighbourd_sum(center_d);
-        if sum as isize <= input {
-            spiral.set(center_d, sum);
-        } else {
-            return sum;
+    fn answer1(input: &isize) -> u32 {
+        let spiral = Spiral::new(*input);
+
+        (spiral.dx.abs() + spiral.dy.abs()) as u32
+    }
+
+    fn answer2(input: &isize) -> u32 {
+        // first pass to compute the required size for the grid here, the values only
+        // outgrowing the input from square 3 on
+        let spiral = Spiral::new((*input).max(3));
+        let required_grid_size = spiral.size;
+
+        // second pass with the correct size
+        let mut spiral = Spiral {
+            size: required_grid_size,
+            grid: vec![0; (required_grid_size * required_grid_size) as usize],
+            ..Spiral::default()
+        };
+
+        let center = (required_grid_size / 2, required_grid_size / 2);
+        spiral.set(center, 1);
+
+        loop {
+            spiral.next();
+            let center_d = (center.0 + spiral.dx, center.1 + spiral.dy);
+            let sum = spiral.neighbourd_sum(center_d);
+            if sum as isize <= *input {
+                spiral.set(center_d, sum);
+            } else {
+                return sum;
+            }
         }
     }
 }
@@ -162,14 +182,25 @@ fn test_spiral_next() {
 
 #[test]
 fn test_answer1() {
-    assert_eq!(answer1(1), 0);
-    assert_eq!(answer1(12), 3);
-    assert_eq!(answer1(23), 2);
-    assert_eq!(answer1(1024), 31);
+    assert_eq!(Day03::answer1(&1), 0);
+    assert_eq!(Day03::answer1(&12), 3);
+    assert_eq!(Day03::answer1(&23), 2);
+    assert_eq!(Day03::answer1(&1024), 31);
 }
 
 #[test]
 fn test_answer2() {
-    assert_eq!(answer2(23), 25);
-    assert_eq!(answer2(747), 806);
+    assert_eq!(Day03::answer2(&1), 2);
+    assert_eq!(Day03::answer2(&23), 25);
+    assert_eq!(Day03::answer2(&747), 806);
+}
+
+#[test]
+fn test_parse_input() {
+    assert_eq!(Day03::parse_input("1\n").unwrap(), 1);
+    assert_eq!(
+        Day03::parse_input("0").unwrap_err().expected,
+        "a square from 1"
+    );
+    assert!(Day03::parse_input("-12").is_err());
 }
@@ -1,32 +1,36 @@
+use crate::{ParseError, Solver};
 use std::collections::HashSet;
 use std::iter::FromIterator;
 
-pub fn title() -> &'static str {
-    "Day 04: High-Entropy Passphrases"
-}
+pub struct Day04;
 
-pub fn answer1(input: &str) -> u32 {
-    let passphrases: Vec<Vec<String>> = parse_input(input);
+impl Solver for Day04 {
+    const DAY: u32 = 4;
+    const TITLE: &'static str = "Day 04: High-Entropy Passphrases";
 
-    passphrases
-        .iter()
-        .fold(0, |count, p| if is_valid(&p) { count + 1 } else { count })
-}
+    type Input = Vec<Vec<String>>;
+    type Output1 = u32;
+    type Output2 = u32;
 
-pub fn answer2(input: &str) -> u32 {
-    let passphrases: Vec<Vec<String>> = parse_input(input);
+    fn parse_input(input: &str) -> Result<Vec<Vec<String>>, ParseError> {
+        let passphrases = input.split('\n');
+        Ok(passphrases
+            .filter(|l| !l.is_empty())
+            .map(|l| l.split(char::is_whitespace).map(String::from).collect())
+            .collect())
+    }
 
-    passphrases
-        .iter()
-        .fold(0, |count, p| if is_valid_2(&p) { count + 1 } else { count })
-}
+    fn answer1(passphrases: &Vec<Vec<String>>) -> u32 {
+        passphrases
+            .iter()
+            .fold(0, |count, p| if is_valid(p) { count + 1 } else { count })
+    }
 
-fn parse_input(input: &str) -> Vec<Vec<String>> {
-    let passphrases = input.split('\n');
-    passphrases
-        .filter(|l| *l != "")
-        .map(|l| l.split(char::is_whitespace).map(String::from).collect())
-        .collect()
+    fn answer2(passphrases: &Vec<Vec<String>>) -> u32 {
+        passphrases
+            .iter()
+            .fold(0, |count, p| if is_valid_2(p) { count + 1 } else { count })
+    }
 }
 
 fn is_valid(passphrase: &[String]) -> bool {
@@ -55,23 +59,14 @@ aa bb cc dd aa
 aa bb cc dd aaa"#,
     );
 
-    assert_eq!(answer1(&input), 2);
+    assert_eq!(Day04::run1(&input).unwrap(), 2);
 }
 
 #[test]
 fn test_is_valid() {
-    assert_eq!(
-        is_valid(&vec_of_strings!["aa", "bb", "cc", "dd", "ee"]),
-        true
-    );
-    assert_eq!(
-        is_valid(&vec_of_strings!["aa", "bb", "cc", "dd", "aa"]),
-        false
-    );
-    assert_eq!(
-        is_valid(&vec_of_strings!["aa", "bb", "cc", "dd", "aaa"]),
-        true
-    );
+    assert!(is_valid(&vec_of_strings!["aa", "bb", "cc", "dd", "ee"]));
+    assert!(!is_valid(&vec_of_strings!["aa", "bb", "cc", "dd", "aa"]));
+    assert!(is_valid(&vec_of_strings!["aa", "bb", "cc", "dd", "aaa"]));
 }
 
 #[test]
@@ -85,23 +80,20 @@ iiii oiii ooii oooi oooo
 oiii ioii iioi iiio"#,
     );
 
-    assert_eq!(answer2(&input), 3);
+    assert_eq!(Day04::run2(&input).unwrap(), 3);
 }
 
 #[test]
 fn test_is_valid_2() {
-    assert_eq!(is_valid_2(&vec_of_strings!["abcde", "fghij"]), true);
-    assert_eq!(is_valid_2(&vec_of_strings!["abcde", "xyz", "ecdab"]), false);
-    assert_eq!(
-        is_valid_2(&vec_of_strings!["a", "ab", "abc", "abd", "abf", "abj"]),
-        true
-    );
-    assert_eq!(
-        is_valid_2(&vec_of_strings!["iiii", "oiii", "ooii", "oooi", "oooo"]),
-        true
-    );
-    assert_eq!(
-        is_valid_2(&vec_of_strings!["oiii", "ioii", "iioi", "iiio"]),
-        false
-    );
+    assert!(is_valid_2(&vec_of_strings!["abcde", "fghij"]));
+    assert!(!is_valid_2(&vec_of_strings!["abcde", "xyz", "ecdab"]));
+    assert!(is_valid_2(&vec_of_strings![
+        "a", "ab", "abc", "abd", "abf", "abj"
+    ]));
+    assert!(is_valid_2(&vec_of_strings![
+        "iiii", "oiii", "ooii", "oooi", "oooo"
+    ]));
+    assert!(!is_valid_2(&vec_of_strings![
+        "oiii", "ioii", "iioi", "iiio"
+    ]));
 }
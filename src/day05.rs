@@ -1,54 +1,70 @@
-pub fn title() -> &'static str {
-    "Day 05: A Maze of Twisty Trampolines, All Alike"
-}
+use crate::common;
+use crate::error::LineError;
+use crate::{ParseError, Solver};
 
-pub fn answer1(input: &str) -> u32 {
-    let mut instructions: Vec<isize> = parse_input(input);
+pub struct Day05;
 
-    let mut current_instruction: isize = 0;
-    let mut jumps = 0;
+impl Solver for Day05 {
+    const DAY: u32 = 5;
+    const TITLE: &'static str = "Day 05: A Maze of Twisty Trampolines, All Alike";
 
-    while 0 <= current_instruction && current_instruction < instructions.len() as isize {
-        let keep: isize = current_instruction;
-        current_instruction = current_instruction + instructions[current_instruction as usize];
-        instructions[keep as usize] += 1;
+    type Input = Vec<isize>;
+    type Output1 = u32;
+    type Output2 = u32;
 
-        jumps += 1;
+    fn parse_input(input: &str) -> Result<Vec<isize>, ParseError> {
+        common::parse_lines(Self::DAY, input, |l| {
+            l.parse::<isize>()
+                .map_err(|_| LineError::new(0, "a jump offset"))
+        })
     }
 
-    jumps
-}
-
-pub fn answer2(input: &str) -> u32 {
-    let mut instructions: Vec<isize> = parse_input(input);
+    fn answer1(instructions: &Vec<isize>) -> u32 {
+        let mut instructions = instructions.clone();
 
-    let mut current_instruction: isize = 0;
-    let mut jumps = 0;
+        let mut current_instruction: isize = 0;
+        let mut jumps = 0;
 
-    while 0 <= current_instruction && current_instruction < instructions.len() as isize {
-        let keep: isize = current_instruction;
-        current_instruction = current_instruction + instructions[current_instruction as usize];
+        while 0 <= current_instruction && current_instruction < instructions.len() as isize {
+            let keep: isize = current_instruction;
+            current_instruction += instructions[current_instruction as usize];
+            instructions[keep as usize] += 1;
 
-        if instructions[keep as usize] >= 3 {
-            instructions[keep as usize] -= 1
-        } else {
-            instructions[keep as usize] += 1
+            jumps += 1;
         }
 
-        jumps += 1;
+        jumps
     }
 
-    jumps
+    fn answer2(instructions: &Vec<isize>) -> u32 {
+        let mut instructions = instructions.clone();
+
+        let mut current_instruction: isize = 0;
+        let mut jumps = 0;
+
+        while 0 <= current_instruction && current_instruction < instructions.len() as isize {
+            let keep: isize = current_instruction;
+            current_instruction += instructions[current_instruction as usize];
+
+            if instructions[keep as usize] >= 3 {
+                instructions[keep as usize] -= 1
+            } else {
+                instructions[keep as usize] += 1
+            }
+
+            jumps += 1;
+        }
+
+        jumps
+    }
 }
 
-fn parse_input(input: &str) -> Vec<isize> {
-    let lines = input.split('\n');
-    lines
-        .filter(|l| *l != "")
-        .map(|l| l.parse::<isize>())
-        .filter(|n| n.is_ok())
-        .map(Result::unwrap) // safe because skip invalid elements
-        .collect()
+#[test]
+fn test_parse_input() {
+    assert_eq!(
+        Day05::parse_input("0\n3\n+-1\n").unwrap_err().to_string(),
+        "day 05, line 3, column 1: expected a jump offset, found `+-1`"
+    );
 }
 
 #[test]
@@ -62,7 +78,7 @@ fn test_answer1() {
 -3"#,
     );
 
-    assert_eq!(answer1(&input), 5);
+    assert_eq!(Day05::run1(&input).unwrap(), 5);
 }
 
 #[test]
@@ -76,5 +92,5 @@ fn test_answer2() {
 -3"#,
     );
 
-    assert_eq!(answer2(&input), 10);
+    assert_eq!(Day05::run2(&input).unwrap(), 10);
 }
@@ -1,45 +1,61 @@
+use crate::common;
+use crate::error::LineError;
+use crate::{ParseError, Solver};
 use std::collections::{HashMap, HashSet};
 
-pub fn title() -> &'static str {
-    "Day 06: Memory Reallocation"
-}
-
-pub fn answer1(input: &str) -> u32 {
-    let mut banks: Vec<u32> = parse_input(input);
-    let mut already_seen = HashSet::new();
-
-    while !already_seen.contains(&banks) {
-        already_seen.insert(banks.clone());
-        next_banks(&mut banks);
+pub struct Day06;
+
+impl Solver for Day06 {
+    const DAY: u32 = 6;
+    const TITLE: &'static str = "Day 06: Memory Reallocation";
+
+    type Input = Vec<u32>;
+    type Output1 = u32;
+    type Output2 = u32;
+
+    fn parse_input(input: &str) -> Result<Vec<u32>, ParseError> {
+        common::parse_line(Self::DAY, input, |line| {
+            let banks = common::words(line)
+                .map(|(offset, n)| {
+                    n.parse::<u32>()
+                        .map_err(|_| LineError::new(offset, "a number of blocks"))
+                })
+                .collect::<Result<Vec<u32>, LineError>>()?;
+            if banks.is_empty() {
+                return Err(LineError::new(0, "at least one bank"));
+            }
+            Ok(banks)
+        })
     }
 
-    already_seen.len() as u32
-}
+    fn answer1(banks: &Vec<u32>) -> u32 {
+        let mut banks = banks.clone();
+        let mut already_seen = HashSet::new();
 
-pub fn answer2(input: &str) -> u32 {
-    let mut banks: Vec<u32> = parse_input(input);
-    let mut already_seen = HashMap::new();
+        while !already_seen.contains(&banks) {
+            already_seen.insert(banks.clone());
+            next_banks(&mut banks);
+        }
 
-    let mut iteration: u32 = 0;
-    while !already_seen.contains_key(&banks) {
-        already_seen.insert(banks.clone(), iteration);
-        next_banks(&mut banks);
-        iteration += 1;
+        already_seen.len() as u32
     }
 
-    iteration - already_seen[&banks]
-}
+    fn answer2(banks: &Vec<u32>) -> u32 {
+        let mut banks = banks.clone();
+        let mut already_seen = HashMap::new();
 
-fn parse_input(input: &str) -> Vec<u32> {
-    input
-        .split(char::is_whitespace)
-        .map(|n| n.parse::<u32>())
-        .filter(|n| n.is_ok())
-        .map(Result::unwrap) // safe because skip invalid elements
-        .collect()
+        let mut iteration: u32 = 0;
+        while !already_seen.contains_key(&banks) {
+            already_seen.insert(banks.clone(), iteration);
+            next_banks(&mut banks);
+            iteration += 1;
+        }
+
+        iteration - already_seen[&banks]
+    }
 }
 
-fn next_banks(bank: &mut Vec<u32>) {
+fn next_banks(bank: &mut [u32]) {
     let idx_max = bank
         .iter()
         .enumerate()
@@ -69,6 +85,15 @@ fn next_banks(bank: &mut Vec<u32>) {
     }
 }
 
+#[test]
+fn test_parse_input() {
+    assert_eq!(Day06::parse_input("0\t2  7 0\n").unwrap(), vec![0, 2, 7, 0]);
+    assert_eq!(
+        Day06::parse_input("").unwrap_err().to_string(),
+        "day 06, line 1, column 1: expected at least one bank, found end of line"
+    );
+}
+
 #[test]
 fn test_next_bank() {
     let mut banks: Vec<u32> = vec![0, 2, 7, 0];
@@ -93,12 +118,12 @@ fn test_next_bank() {
 fn test_answer1() {
     let input = String::from(r#"0 2 7 0"#);
 
-    assert_eq!(answer1(&input), 5);
+    assert_eq!(Day06::run1(&input).unwrap(), 5);
 }
 
 #[test]
 fn test_answer2() {
     let input = String::from(r#"0 2 7 0"#);
 
-    assert_eq!(answer2(&input), 4);
+    assert_eq!(Day06::run2(&input).unwrap(), 4);
 }
@@ -1,3 +1,6 @@
+use crate::common;
+use crate::error::LineError;
+use crate::{ParseError, Solver};
 use nom::types::CompleteStr;
 use nom::{
     char, delimited, do_parse, is_not, map, map_res, named, opt, separated_nonempty_list, tag,
@@ -5,13 +8,12 @@ use nom::{
 use petgraph::graph::NodeIndex;
 use petgraph::{Direction, Graph};
 use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
 
-pub fn title() -> &'static str {
-    "Day 07: Recursive Circus"
-}
+pub struct Day07;
 
 #[derive(Debug, PartialEq)]
-struct Line {
+pub struct Line {
     name: String,
     weight: u32,
     children: Option<Vec<String>>,
@@ -24,7 +26,7 @@ named!(
 
 named!(
     weight_parser<CompleteStr, u32>,
-    map_res!(delimited!(char!('('), is_not!(")"), char!(')')), |CompleteStr(s)| u32::from_str_radix(s, 10))
+    map_res!(delimited!(char!('('), is_not!(")"), char!(')')), |CompleteStr(s)| s.parse::<u32>())
 );
 
 named!(
@@ -42,8 +44,8 @@ named!(
             >> children: opt!(children_parser)
             >> (Line {
                 name: name.to_string(),
-                weight: weight,
-                children: children
+                weight,
+                children
             })
     )
 );
@@ -71,8 +73,8 @@ impl Towers {
 
         // add all the edges
         for line in lines {
-            if line.children.is_some() {
-                for child in &line.children.clone().unwrap() {
+            if let Some(children) = &line.children {
+                for child in children {
                     graph.update_edge(nodes[&line.name], nodes[child], 0); //0: no edge weight
                 }
             }
@@ -115,7 +117,7 @@ impl Towers {
     }
 
     fn update_node_value(&mut self, node: NodeIndex, weight: u32) {
-        let mut node = self.graph.node_weight_mut(node).unwrap();
+        let node = self.graph.node_weight_mut(node).unwrap();
         node.2 = weight;
     }
 
@@ -125,9 +127,8 @@ impl Towers {
             let children_weight: u32 = self.children_weight(current_node);
             self.update_node_value(current_node, children_weight);
 
-            let parent = self.parent(current_node);
-            if parent.is_some() {
-                parents_to_update.insert(parent.unwrap());
+            if let Some(parent) = self.parent(current_node) {
+                parents_to_update.insert(parent);
             }
         }
 
@@ -137,6 +138,8 @@ impl Towers {
         }
     }
 
+    // None when the children are balanced, or when two of them are too few to tell
+    // which one is wrong
     fn find_unbalanced_node(&self, current_node: NodeIndex) -> Option<(NodeIndex, u32)> {
         // finding the unbalanced side
         let mut children_weight_frequency: HashMap<u32, usize> = HashMap::new();
@@ -157,14 +160,12 @@ impl Towers {
         let unbalanced_side: u32 = children_weight_frequency
             .iter()
             .find(|(_, f)| **f == 1)
-            .map(|(w, _)| *w)
-            .unwrap();
+            .map(|(w, _)| *w)?;
         let correct_side: u32 = children_weight_frequency
             .iter()
             .find(|(_, f)| **f > 1)
-            .map(|(w, _)| *w)
-            .unwrap();
-        let weight_diff: i32 = unbalanced_side as i32 - correct_side as i32;
+            .map(|(w, _)| *w)?;
+        let weight_diff: i64 = i64::from(unbalanced_side) - i64::from(correct_side);
 
         let unbalanced_side: NodeIndex = children_weight_per_node[&unbalanced_side][0];
 
@@ -178,43 +179,136 @@ impl Towers {
                 .and_modify(|f| *f += 1)
                 .or_insert(1);
         }
-        if children_weight_frequency.len() == 1 {
+        if children_weight_frequency.len() <= 1 {
             // children balanced, so the current node is the unbalanced one
-            let new_weight = (self.get(unbalanced_side).1 as i32 - weight_diff) as u32;
-            Some((unbalanced_side, new_weight))
+            let new_weight = i64::from(self.get(unbalanced_side).1) - weight_diff;
+            u32::try_from(new_weight)
+                .ok()
+                .map(|weight| (unbalanced_side, weight))
         } else {
             self.find_unbalanced_node(unbalanced_side)
         }
     }
 }
 
-pub fn answer1(input: &str) -> String {
-    let lines = parse_input(&input);
-    let towers = Towers::new(&lines);
+// the lines of the input, with their number
+fn numbered_lines(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input
+        .split('\n')
+        .enumerate()
+        .filter(|(_, l)| !l.is_empty())
+        .map(|(n, l)| (n + 1, l))
+}
 
-    towers.get(towers.source()).0
+// the offset of a child in the list of a line
+fn child_offset(text: &str, child: &str) -> usize {
+    let start = text.find(" -> ").map_or(0, |i| i + 4);
+    text[start..]
+        .split(", ")
+        .find(|c| *c == child)
+        .map_or(start, |c| common::offset(text, c))
 }
 
-pub fn answer2(input: &str) -> u32 {
-    let lines = parse_input(&input);
-    let mut towers = Towers::new(&lines);
+// every program is defined once, and is held by another one but the bottom one
+fn check_tower(input: &str, lines: &[Line]) -> Result<(), ParseError> {
+    let error = |n, text: &str, offset, expected| {
+        Err(ParseError::new(
+            Day07::DAY,
+            n,
+            text,
+            LineError::new(offset, expected),
+        ))
+    };
 
-    // starting for the leaf nodes of the graph, update all the weights
-    // a node weight will be it's weight + the sum of it's childrens
-    towers.compute_children_weights(towers.leafs());
+    let mut defined: HashSet<&str> = HashSet::new();
+    for ((n, text), line) in numbered_lines(input).zip(lines) {
+        if !defined.insert(&line.name) {
+            return error(n, text, 0, "a program defined once");
+        }
+    }
+
+    let mut parents: HashMap<&str, &str> = HashMap::new();
+    for ((n, text), line) in numbered_lines(input).zip(lines) {
+        for child in line.children.iter().flatten() {
+            if !defined.contains(child.as_str()) {
+                return error(
+                    n,
+                    text,
+                    child_offset(text, child),
+                    "a program defined on its own line",
+                );
+            }
+            if parents.insert(child, &line.name).is_some() {
+                return error(
+                    n,
+                    text,
+                    child_offset(text, child),
+                    "a program held by a single other one",
+                );
+            }
+        }
+    }
+
+    // walking down from a program held by another one ends at the bottom, or loops
+    let mut bottom = None;
+    for ((n, text), line) in numbered_lines(input).zip(lines) {
+        let mut name = line.name.as_str();
+        let mut steps = 0;
+        while let Some(parent) = parents.get(name) {
+            name = parent;
+            steps += 1;
+            if steps > lines.len() {
+                return error(n, text, 0, "a program in a tower without cycles");
+            }
+        }
+        match bottom {
+            None => bottom = Some(name),
+            Some(bottom) if bottom == name => {}
+            Some(_) => return error(n, text, 0, "a program in the same tower as the others"),
+        }
+    }
 
-    // for all nodes of the graph starting from the leaf,
-    // find the first node for which the children don't have the same weight
-    towers.find_unbalanced_node(towers.source()).unwrap().1
+    Ok(())
 }
 
-fn parse_input(input: &str) -> Vec<Line> {
-    let lines = input.split('\n');
+impl Solver for Day07 {
+    const DAY: u32 = 7;
+    const TITLE: &'static str = "Day 07: Recursive Circus";
+
+    type Input = Vec<Line>;
+    type Output1 = String;
+    type Output2 = Option<u32>;
+
+    fn parse_input(input: &str) -> Result<Vec<Line>, ParseError> {
+        let lines = common::parse_lines(Self::DAY, input, |l| {
+            common::parse_nom(l, line_parser, "`name (weight) -> children`")
+        })?;
+        if lines.is_empty() {
+            return Err(common::missing(Self::DAY, input, "a program tree"));
+        }
+        check_tower(input, &lines)?;
+        Ok(lines)
+    }
+
+    fn answer1(lines: &Vec<Line>) -> String {
+        let towers = Towers::new(lines);
+
+        towers.get(towers.source()).0
+    }
+
+    fn answer2(lines: &Vec<Line>) -> Option<u32> {
+        let mut towers = Towers::new(lines);
 
-    lines
-        .filter(|l| *l != "")
-        .map(|l| line_parser(CompleteStr(l)).unwrap().1)
-        .collect()
+        // starting for the leaf nodes of the graph, update all the weights
+        // a node weight will be it's weight + the sum of it's childrens
+        towers.compute_children_weights(towers.leafs());
+
+        // for all nodes of the graph starting from the leaf,
+        // find the first node for which the children don't have the same weight
+        towers
+            .find_unbalanced_node(towers.source())
+            .map(|(_, weight)| weight)
+    }
 }
 
 #[test]
@@ -274,6 +368,65 @@ fn parse_line_with_children() {
     );
 }
 
+#[test]
+fn test_parse_input() {
+    assert_eq!(
+        Day07::parse_input("pbga (66)\nfwft (7x2) -> ktlj").unwrap_err(),
+        ParseError {
+            day: 7,
+            line: 2,
+            column: 6,
+            text: "(7x2)".to_string(),
+            expected: "`name (weight) -> children`",
+        }
+    );
+    assert_eq!(
+        Day07::parse_input("fwft (72) -> ktlj,")
+            .unwrap_err()
+            .to_string(),
+        "day 07, line 1, column 18: expected end of line, found `,`"
+    );
+    assert_eq!(
+        Day07::parse_input("\n").unwrap_err().expected,
+        "a program tree"
+    );
+}
+
+#[test]
+fn test_parse_input_tower() {
+    let error = |input| Day07::parse_input(input).unwrap_err().to_string();
+    assert_eq!(
+        error("a (1) -> b, c\nb (2)"),
+        "day 07, line 1, column 13: expected a program defined on its own line, found `c`"
+    );
+    assert_eq!(
+        error("a (1)\nb (2) -> a\na (3)"),
+        "day 07, line 3, column 1: expected a program defined once, found `a`"
+    );
+    assert_eq!(
+        error("a (1) -> c\nb (2) -> c\nc (3)"),
+        "day 07, line 2, column 10: expected a program held by a single other one, found `c`"
+    );
+    assert_eq!(
+        error("a (1) -> b\nb (2) -> a"),
+        "day 07, line 1, column 1: expected a program in a tower without cycles, found `a`"
+    );
+    assert_eq!(
+        error("a (1)\nb (2)"),
+        "day 07, line 2, column 1: expected a program in the same tower as the others, found `b`"
+    );
+}
+
+#[test]
+fn test_answer2_balanced() {
+    assert_eq!(
+        Day07::run2("a (1) -> b, c, d\nb (2)\nc (2)\nd (2)").unwrap(),
+        None
+    );
+    assert_eq!(Day07::run2("a (1) -> b, c\nb (2)\nc (3)").unwrap(), None);
+    assert_eq!(Day07::run2("a (1)").unwrap(), None);
+}
+
 #[test]
 fn test_answer1() {
     let input = String::from(
@@ -293,7 +446,7 @@ gyxo (61)
 cntj (57)"#,
     );
 
-    assert_eq!(answer1(&input), "tknk");
+    assert_eq!(Day07::run1(&input).unwrap(), "tknk");
 }
 
 #[test]
@@ -314,7 +467,7 @@ ugml (68) -> gyxo, ebii, jptl
 gyxo (61)
 cntj (57)"#,
     );
-    let lines = parse_input(&input);
+    let lines = Day07::parse_input(&input).unwrap();
     let mut towers = Towers::new(&lines);
 
     towers.compute_children_weights(towers.leafs());
@@ -381,7 +534,7 @@ gyxo (61)
 cntj (57)"#,
     );
 
-    assert_eq!(answer2(&input), 60);
+    assert_eq!(Day07::run2(&input).unwrap(), Some(60));
 }
 
 #[test]
@@ -403,5 +556,5 @@ gyxo (61)
 cntj (57)"#,
     );
 
-    assert_eq!(answer2(&input), 60);
+    assert_eq!(Day07::run2(&input).unwrap(), Some(60));
 }
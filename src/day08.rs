@@ -1,86 +1,62 @@
+use crate::common;
+use crate::error::LineError;
+use crate::vm::{self, Bytecode, Cmp, Fault, Guard, Op, Operand, Vm};
+use crate::{ParseError, Solver};
 use nom::types::CompleteStr;
-use nom::{char, do_parse, map, map_res, named, opt, recognize, tag, take_until, tuple};
-use std::collections::HashMap;
+use nom::{
+    alt, char, do_parse, many0, map, map_res, named, opt, preceded, recognize, return_error, tag,
+    take_until, tuple,
+};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::str::FromStr;
 
-pub fn title() -> &'static str {
-    "Day 08: I Heard You Like Register"
-}
+pub struct Day08;
 
-#[derive(Debug, PartialEq)]
-enum Direction {
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operation {
     Inc,
     Dec,
+    Set,
+    Mul,
+    Mod,
 }
 
-impl FromStr for Direction {
+impl FromStr for Operation {
     type Err = ();
 
-    fn from_str(s: &str) -> Result<Direction, ()> {
+    fn from_str(s: &str) -> Result<Operation, ()> {
         match s {
-            "inc" => Ok(Direction::Inc),
-            "dec" => Ok(Direction::Dec),
+            "inc" => Ok(Operation::Inc),
+            "dec" => Ok(Operation::Dec),
+            "set" => Ok(Operation::Set),
+            "mul" => Ok(Operation::Mul),
+            "mod" => Ok(Operation::Mod),
             _ => Err(()),
         }
     }
 }
 
-impl Direction {
-    fn apply(&self, reg: i32, val: i32) -> i32 {
-        match self {
-            Direction::Inc => reg + val,
-            Direction::Dec => reg - val,
-        }
-    }
-}
-
 #[derive(Debug, PartialEq)]
-enum Predicate {
-    Eq,
-    NotEq,
-    Greater,
-    Smaller,
-    GreaterEq,
-    SmallerEq,
+enum Value {
+    Register(String),
+    Number(i64),
 }
 
-impl FromStr for Predicate {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Predicate, ()> {
-        match s {
-            "==" => Ok(Predicate::Eq),
-            "!=" => Ok(Predicate::NotEq),
-            ">" => Ok(Predicate::Greater),
-            "<" => Ok(Predicate::Smaller),
-            ">=" => Ok(Predicate::GreaterEq),
-            "<=" => Ok(Predicate::SmallerEq),
-            _ => Err(()),
-        }
-    }
-}
-
-impl Predicate {
-    fn validate(&self, reg: i32, val: i32) -> bool {
-        match self {
-            Predicate::Eq => reg == val,
-            Predicate::NotEq => reg != val,
-            Predicate::Greater => reg > val,
-            Predicate::Smaller => reg < val,
-            Predicate::GreaterEq => reg >= val,
-            Predicate::SmallerEq => reg <= val,
-        }
-    }
+#[derive(Debug, PartialEq)]
+enum Condition {
+    Compare(Value, Cmp, Value),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
 }
 
+/// `register operation value`, with an optional `if` condition.
 #[derive(Debug, PartialEq)]
-struct Instruction {
-    reg_inc: String,
-    inc: Direction,
-    inc_val: i32,
-    reg_pred: String,
-    pred: Predicate,
-    pred_val: i32,
+pub struct Instruction {
+    register: String,
+    operation: Operation,
+    value: Value,
+    condition: Option<Condition>,
 }
 
 named!(
@@ -89,116 +65,287 @@ named!(
 );
 
 named!(
-    val_parser<CompleteStr, i32>,
-    map_res!(recognize!(tuple!(opt!(char!('-')), nom::digit)), |CompleteStr(s)| i32::from_str_radix(s, 10))
+    number_parser<CompleteStr, i64>,
+    map_res!(recognize!(tuple!(opt!(char!('-')), nom::digit)), |CompleteStr(s)| s.parse::<i64>())
 );
 
 named!(
-    direction_parser<CompleteStr, Direction>,
-    map_res!(take_until!(" "), |CompleteStr(s)| Direction::from_str(s))
+    value_parser<CompleteStr, Value>,
+    alt!(number_parser => { Value::Number } | reg_parser => { Value::Register })
 );
 
 named!(
-    predicate_parser<CompleteStr, Predicate>,
-    map_res!(take_until!(" "), |CompleteStr(s)| Predicate::from_str(s))
+    operation_parser<CompleteStr, Operation>,
+    map_res!(take_until!(" "), |CompleteStr(s)| Operation::from_str(s))
 );
 
 named!(
-    instruction_parser<CompleteStr, Instruction>,
+    cmp_parser<CompleteStr, Cmp>,
+    map_res!(take_until!(" "), |CompleteStr(s)| Cmp::from_str(s))
+);
+
+named!(
+    comparison_parser<CompleteStr, Condition>,
     do_parse!(
-        reg_inc: reg_parser
+        left: value_parser
             >> tag!(" ")
-            >> inc: direction_parser
+            >> cmp: cmp_parser
             >> tag!(" ")
-            >> inc_val: val_parser
-            >> tag!(" if ")
-            >> reg_pred: reg_parser
+            >> right: value_parser
+            >> (Condition::Compare(left, cmp, right))
+    )
+);
+
+// `and` binds tighter than `or`
+named!(
+    conjunction_parser<CompleteStr, Condition>,
+    do_parse!(
+        first: comparison_parser
+            >> rest: many0!(preceded!(tag!(" and "), comparison_parser))
+            >> (rest.into_iter().fold(first, |a, b| Condition::And(Box::new(a), Box::new(b))))
+    )
+);
+
+named!(
+    condition_parser<CompleteStr, Condition>,
+    do_parse!(
+        first: conjunction_parser
+            >> rest: many0!(preceded!(tag!(" or "), conjunction_parser))
+            >> (rest.into_iter().fold(first, |a, b| Condition::Or(Box::new(a), Box::new(b))))
+    )
+);
+
+named!(
+    instruction_parser<CompleteStr, Instruction>,
+    do_parse!(
+        register: reg_parser
             >> tag!(" ")
-            >> pred: predicate_parser
+            >> operation: operation_parser
             >> tag!(" ")
-            >> pred_val: val_parser
+            >> value: value_parser
+            >> condition: opt!(preceded!(tag!(" if "), return_error!(condition_parser)))
             >> (Instruction {
-                reg_inc: reg_inc.to_string(),
-                inc: inc,
-                inc_val: inc_val,
-                reg_pred: reg_pred,
-                pred: pred,
-                pred_val: pred_val
+                register,
+                operation,
+                value,
+                condition
             })
     )
 );
 
-pub fn answer1(input: &str) -> i32 {
-    let instructions = parse_input(&input);
+fn parse_instruction(line: &str) -> Result<Instruction, LineError> {
+    let instruction = common::parse_nom(
+        line,
+        instruction_parser,
+        "`register inc|dec|set|mul|mod value [if condition]`",
+    )?;
+    if let (Operation::Mod, Value::Number(0)) = (instruction.operation, &instruction.value) {
+        let (offset, _) = common::words(line).nth(2).unwrap();
+        return Err(LineError::new(offset, "a divisor other than 0"));
+    }
+    Ok(instruction)
+}
 
-    // initialise all the registers
-    let mut registers: HashMap<String, i32> = HashMap::new();
-    for instruction in &instructions {
-        registers.insert(instruction.reg_inc.clone(), 0);
-        registers.insert(instruction.reg_pred.clone(), 0);
+fn operand(bytecode: &mut Bytecode, value: &Value) -> Operand {
+    match value {
+        Value::Register(name) => Operand::Reg(bytecode.register(name)),
+        Value::Number(n) => Operand::Val(*n),
     }
+}
 
-    // run the program
-    for instruction in &instructions {
-        if instruction
-            .pred
-            .validate(registers[&instruction.reg_pred], instruction.pred_val)
-        {
-            registers.insert(
-                instruction.reg_inc.clone(),
-                instruction
-                    .inc
-                    .apply(registers[&instruction.reg_inc], instruction.inc_val),
-            );
-        }
+fn compile_condition(bytecode: &mut Bytecode, condition: &Condition) -> vm::Condition {
+    let mut compile = |c| Box::new(compile_condition(bytecode, c));
+    match condition {
+        Condition::Compare(left, cmp, right) => vm::Condition::Guard(Guard {
+            left: operand(bytecode, left),
+            cmp: *cmp,
+            right: operand(bytecode, right),
+        }),
+        Condition::And(a, b) => vm::Condition::And(compile(a), compile(b)),
+        Condition::Or(a, b) => vm::Condition::Or(compile(a), compile(b)),
     }
+}
 
-    // find the max value
-    registers
-        .iter()
-        .max_by_key(|(_k, v)| *v)
-        .map(|(_k, v)| *v)
-        .unwrap()
+// registers get a slot the first time they are seen
+fn compile(bytecode: &mut Bytecode, instruction: &Instruction) {
+    let reg = bytecode.register(&instruction.register);
+    let value = operand(bytecode, &instruction.value);
+    let op = match instruction.operation {
+        Operation::Inc => Op::Add(reg, value),
+        Operation::Dec => Op::Sub(reg, value),
+        Operation::Set => Op::Set(reg, value),
+        Operation::Mul => Op::Mul(reg, value),
+        Operation::Mod => Op::Mod(reg, value),
+    };
+    let condition = instruction
+        .condition
+        .as_ref()
+        .map(|c| compile_condition(bytecode, c));
+    bytecode.push(op, condition);
 }
 
-pub fn answer2(input: &str) -> i32 {
-    let instructions = parse_input(&input);
+fn compile_all(instructions: &[Instruction]) -> Bytecode {
+    let mut bytecode = Bytecode::default();
+    for instruction in instructions {
+        compile(&mut bytecode, instruction);
+    }
+    bytecode
+}
 
-    // initialise all the registers
-    let mut registers: HashMap<String, i32> = HashMap::new();
-    for instruction in &instructions {
-        registers.insert(instruction.reg_inc.clone(), 0);
-        registers.insert(instruction.reg_pred.clone(), 0);
+/// The values written to a register, by the instructions whose condition held.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct History {
+    pub timeline: Vec<(usize, i64)>, // the index of the instruction, and the value it wrote
+    pub min: Option<(i64, usize)>,   // the smallest value written, first written at this index
+    pub max: Option<(i64, usize)>,
+}
+
+impl History {
+    fn write(&mut self, index: usize, value: i64) {
+        self.timeline.push((index, value));
+        if self.min.is_none_or(|(min, _)| value < min) {
+            self.min = Some((value, index));
+        }
+        if self.max.is_none_or(|(max, _)| value > max) {
+            self.max = Some((value, index));
+        }
     }
 
-    // run the program
-    let mut max = 0;
-    for instruction in &instructions {
-        if instruction
-            .pred
-            .validate(registers[&instruction.reg_pred], instruction.pred_val)
-        {
-            let apply = instruction
-                .inc
-                .apply(registers[&instruction.reg_inc], instruction.inc_val);
-            registers.insert(instruction.reg_inc.clone(), apply);
-            if max < apply {
-                max = apply;
-            }
+    pub fn writes(&self) -> usize {
+        self.timeline.len()
+    }
+}
+
+/// What happened to the registers while running a program.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace {
+    pub registers: BTreeMap<String, History>, // every register, even the ones never written
+    pub never_fired: Vec<usize>,              // the instructions whose condition never held
+    pub fault: Option<(usize, Fault)>,        // the instruction that stopped the program
+}
+
+/// Run the instructions, recording every write to a register.
+pub fn trace(instructions: &[Instruction]) -> Trace {
+    let bytecode = compile_all(instructions);
+    let mut histories = vec![History::default(); bytecode.names().len()];
+    let mut vm = Vm::new(&bytecode);
+    // there are no jumps, the program ends after its last instruction
+    while let Some(&fired) = vm.fired().get(vm.pc() as usize) {
+        let pc = vm.pc() as usize;
+        vm.step(&mut ());
+        if vm.fault().is_some() {
+            break;
+        }
+        if vm.fired()[pc] > fired {
+            let slot = bytecode.slot(&instructions[pc].register).unwrap();
+            histories[slot].write(pc, vm.registers()[slot]);
         }
     }
 
-    // find the max value
-    max
+    let never_fired = (0..instructions.len())
+        .filter(|i| instructions[*i].condition.is_some() && vm.fired()[*i] == 0)
+        .collect();
+    Trace {
+        registers: bytecode.names().iter().cloned().zip(histories).collect(),
+        never_fired,
+        fault: vm.fault().map(|fault| (vm.pc() as usize, fault)),
+    }
 }
 
-fn parse_input(input: &str) -> Vec<Instruction> {
-    let lines = input.split('\n');
+/// Why a line could not be run.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExecuteError {
+    Parse(LineError),
+    Fault(Fault), // the registers are unchanged
+}
 
-    lines
-        .filter(|l| *l != "")
-        .map(|l| instruction_parser(CompleteStr(l)).unwrap().1)
-        .collect()
+/// Run instructions one at a time, as they come.
+#[derive(Debug, Default)]
+pub struct Interpreter {
+    bytecode: Bytecode, // only there to give the registers their slot
+    registers: Vec<i64>,
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Interpreter::default()
+    }
+
+    /// Parse and run a line, e.g. `a set 5 if b > 1 or c == 0`.
+    pub fn execute(&mut self, line: &str) -> Result<(), ExecuteError> {
+        let instruction = parse_instruction(line).map_err(ExecuteError::Parse)?;
+        compile(&mut self.bytecode, &instruction);
+        let instr = self.bytecode.code.pop().unwrap();
+
+        self.registers.resize(self.bytecode.names().len(), 0);
+        vm::execute(&instr, &mut self.registers).map_err(ExecuteError::Fault)
+    }
+
+    /// The value of a register, 0 for the ones never used.
+    pub fn register(&self, name: &str) -> i64 {
+        self.bytecode
+            .slot(name)
+            .and_then(|slot| self.registers.get(slot))
+            .cloned()
+            .unwrap_or(0)
+    }
+
+    /// The registers, in the order they were first used.
+    pub fn registers(&self) -> impl Iterator<Item = (&str, i64)> {
+        self.bytecode
+            .names()
+            .iter()
+            .map(move |name| (name.as_str(), self.register(name)))
+    }
+}
+
+// `a=1 b=0 c=-10`
+impl fmt::Display for Interpreter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let registers: Vec<String> = self
+            .registers()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+        write!(f, "{}", registers.join(" "))
+    }
+}
+
+impl Solver for Day08 {
+    const DAY: u32 = 8;
+    const TITLE: &'static str = "Day 08: I Heard You Like Register";
+
+    type Input = Vec<Instruction>;
+    type Output1 = Option<i64>;
+    type Output2 = Option<i64>;
+
+    fn parse_input(input: &str) -> Result<Vec<Instruction>, ParseError> {
+        common::parse_lines(Self::DAY, input, parse_instruction)
+    }
+
+    // no answer when an instruction cannot be run
+    fn answer1(instructions: &Vec<Instruction>) -> Option<i64> {
+        let bytecode = compile_all(instructions);
+        let mut vm = Vm::new(&bytecode);
+        vm.run(&mut ());
+        match vm.fault() {
+            Some(_) => None,
+            None => Some(vm.registers().iter().cloned().max().unwrap_or(0)),
+        }
+    }
+
+    // the registers start at 0
+    fn answer2(instructions: &Vec<Instruction>) -> Option<i64> {
+        let trace = trace(instructions);
+        if trace.fault.is_some() {
+            return None;
+        }
+        let max = trace
+            .registers
+            .values()
+            .filter_map(|history| history.max)
+            .fold(0, |max, (value, _)| max.max(value));
+        Some(max)
+    }
 }
 
 #[test]
@@ -208,12 +355,14 @@ fn parse_simple_instruction() {
         Ok((
             CompleteStr::from(""),
             Instruction {
-                reg_inc: "b".to_string(),
-                inc: Direction::Inc,
-                inc_val: 5,
-                reg_pred: "a".to_string(),
-                pred: Predicate::Greater,
-                pred_val: 1
+                register: "b".to_string(),
+                operation: Operation::Inc,
+                value: Value::Number(5),
+                condition: Some(Condition::Compare(
+                    Value::Register("a".to_string()),
+                    Cmp::Gt,
+                    Value::Number(1)
+                )),
             }
         ))
     );
@@ -223,17 +372,113 @@ fn parse_simple_instruction() {
         Ok((
             CompleteStr::from(""),
             Instruction {
-                reg_inc: "bad".to_string(),
-                inc: Direction::Dec,
-                inc_val: -5,
-                reg_pred: "sdqa".to_string(),
-                pred: Predicate::GreaterEq,
-                pred_val: -111
+                register: "bad".to_string(),
+                operation: Operation::Dec,
+                value: Value::Number(-5),
+                condition: Some(Condition::Compare(
+                    Value::Register("sdqa".to_string()),
+                    Cmp::Ge,
+                    Value::Number(-111)
+                )),
             }
         ))
     );
 }
 
+#[test]
+fn parse_extended_instruction() {
+    let compare = |left: &str, cmp, right| {
+        Box::new(Condition::Compare(
+            Value::Register(left.to_string()),
+            cmp,
+            Value::Number(right),
+        ))
+    };
+    assert_eq!(
+        parse_instruction("a set b if x > 1 or y < 2 and z == 3"),
+        Ok(Instruction {
+            register: "a".to_string(),
+            operation: Operation::Set,
+            value: Value::Register("b".to_string()),
+            condition: Some(Condition::Or(
+                compare("x", Cmp::Gt, 1),
+                Box::new(Condition::And(
+                    compare("y", Cmp::Lt, 2),
+                    compare("z", Cmp::Eq, 3)
+                ))
+            )),
+        })
+    );
+    assert_eq!(parse_instruction("a mul 2").map(|i| i.condition), Ok(None));
+    assert_eq!(
+        parse_instruction("a mod 0 if b > 1"),
+        Err(LineError::new(6, "a divisor other than 0"))
+    );
+
+    assert_eq!(
+        Day08::parse_input("a inc 1\na div 2").unwrap_err().to_string(),
+        "day 08, line 2, column 3: expected `register inc|dec|set|mul|mod value [if condition]`, found `div`"
+    );
+    assert_eq!(
+        Day08::parse_input("a inc 1 if b >> 2")
+            .unwrap_err()
+            .to_string(),
+        "day 08, line 1, column 14: expected `register inc|dec|set|mul|mod value [if condition]`, found `>>`"
+    );
+}
+
+#[test]
+fn test_interpreter() {
+    let mut interpreter = Interpreter::new();
+    interpreter.execute("a set 5").unwrap();
+    interpreter.execute("b inc a if a > 1 and c == 0").unwrap();
+    interpreter.execute("a mul b if b < 0 or a >= 5").unwrap();
+    assert_eq!(interpreter.to_string(), "a=25 b=5 c=0");
+
+    interpreter.execute("c dec 1 if d != 0").unwrap();
+    assert_eq!(interpreter.to_string(), "a=25 b=5 c=0 d=0");
+    assert_eq!(interpreter.register("e"), 0);
+    assert!(interpreter.execute("c jmp 1").is_err());
+    assert_eq!(interpreter.registers().count(), 4);
+
+    assert_eq!(
+        interpreter.execute("a mod e"),
+        Err(ExecuteError::Fault(Fault::DivisionByZero))
+    );
+    interpreter.execute("a set 9223372036854775807").unwrap();
+    assert_eq!(
+        interpreter.execute("a inc 1"),
+        Err(ExecuteError::Fault(Fault::Overflow))
+    );
+    assert_eq!(
+        interpreter.to_string(),
+        "a=9223372036854775807 b=5 c=0 d=0 e=0"
+    );
+}
+
+#[test]
+fn test_trace() {
+    let instructions = Day08::parse_input(
+        "b inc 5 if a > 1\na inc 1 if b < 5\nc dec -10 if a >= 1\nc inc -20 if c == 10\nc set 10",
+    )
+    .unwrap();
+    let trace = trace(&instructions);
+    assert_eq!(trace.never_fired, vec![0]);
+    assert_eq!(trace.fault, None);
+    assert_eq!(
+        trace.registers.keys().collect::<Vec<_>>(),
+        vec!["a", "b", "c"]
+    );
+    assert_eq!(trace.registers["b"], History::default());
+
+    let c = &trace.registers["c"];
+    assert_eq!(c.timeline, vec![(2, 10), (3, -10), (4, 10)]);
+    assert_eq!(
+        (c.min, c.max, c.writes()),
+        (Some((-10, 3)), Some((10, 2)), 3)
+    );
+}
+
 #[test]
 fn test_answer1() {
     let input = String::from(
@@ -244,7 +489,8 @@ c dec -10 if a >= 1
 c inc -20 if c == 10"#,
     );
 
-    assert_eq!(answer1(&input), 1);
+    assert_eq!(Day08::run1(&input).unwrap(), Some(1));
+    assert_eq!(Day08::run1("a inc 1\na mod b").unwrap(), None);
 }
 
 #[test]
@@ -257,5 +503,9 @@ c dec -10 if a >= 1
 c inc -20 if c == 10"#,
     );
 
-    assert_eq!(answer2(&input), 10);
+    assert_eq!(Day08::run2(&input).unwrap(), Some(10));
+
+    let trace = trace(&Day08::parse_input("a inc 3\na mod b\na inc 1").unwrap());
+    assert_eq!(trace.fault, Some((1, Fault::DivisionByZero)));
+    assert_eq!(trace.registers["a"].timeline, vec![(0, 3)]);
 }
@@ -1,137 +1,538 @@
-use nom::types::CompleteStr;
-use nom::{many0, map_res, named, take};
-use std::str::FromStr;
-
-pub fn title() -> &'static str {
-    "Day 09: Stream Processing"
-}
-
-#[derive(Debug, PartialEq)]
-enum Char {
-    OpenStream,
-    CloseStream,
-    OpenGarbage,
-    CloseGarbage,
-    Negate,
-    Other,
-}
-
-impl FromStr for Char {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Char, ()> {
-        use self::Char::*;
-        match s {
-            "{" => Ok(OpenStream),
-            "}" => Ok(CloseStream),
-            "<" => Ok(OpenGarbage),
-            ">" => Ok(CloseGarbage),
-            "!" => Ok(Negate),
-            _ => Ok(Other),
+use crate::common;
+use crate::error::LineError;
+use crate::{ParseError, Solver};
+use std::cmp::Reverse;
+use std::fmt;
+use std::io::{self, BufReader, Read};
+use std::ops::Range;
+
+pub struct Day09;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Group,
+    Garbage,
+    Cancelled, // in garbage, after a `!`
+}
+
+/// The score of the groups of a stream, and the number of characters of its garbage.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Totals {
+    pub score: u64,
+    pub garbage: u64,
+}
+
+/// What a byte of a stream is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Token {
+    Open,         // `{`
+    Close,        // `}`
+    Stray,        // `}` without a group to close
+    GarbageStart, // `<`
+    GarbageEnd,   // `>`
+    Garbage,      // a character of garbage
+    Cancel,       // `!` in garbage
+    Cancelled,    // the character after it
+    Other,        // between the groups, like `,`
+}
+
+/// A single pass over a stream, one byte at a time, in constant memory.
+#[derive(Debug)]
+pub struct Processor {
+    mode: Mode,
+    depth: u64,
+    totals: Totals,
+}
+
+impl Default for Processor {
+    fn default() -> Self {
+        Processor {
+            mode: Mode::Group,
+            depth: 0,
+            totals: Totals::default(),
         }
     }
 }
 
-named!(
-    take1<CompleteStr, Char>,
-    map_res!(take!(1), |CompleteStr(s)| Char::from_str(s))
- );
+impl Processor {
+    pub fn new() -> Self {
+        Processor::default()
+    }
+
+    pub fn feed(&mut self, byte: u8) -> Token {
+        let (mode, token) = match (self.mode, byte) {
+            (Mode::Group, b'{') => {
+                self.depth += 1;
+                (Mode::Group, Token::Open)
+            }
+            (Mode::Group, b'}') if self.depth > 0 => {
+                self.totals.score += self.depth;
+                self.depth -= 1;
+                (Mode::Group, Token::Close)
+            }
+            // a stray `}` closes nothing
+            (Mode::Group, b'}') => (Mode::Group, Token::Stray),
+            (Mode::Group, b'<') => (Mode::Garbage, Token::GarbageStart),
+            (Mode::Group, _) => (Mode::Group, Token::Other),
+            (Mode::Garbage, b'!') => (Mode::Cancelled, Token::Cancel),
+            (Mode::Garbage, b'>') => (Mode::Group, Token::GarbageEnd),
+            (Mode::Garbage, _) => {
+                self.totals.garbage += 1;
+                (Mode::Garbage, Token::Garbage)
+            }
+            (Mode::Cancelled, _) => (Mode::Garbage, Token::Cancelled),
+        };
+        self.mode = mode;
+        token
+    }
+
+    pub fn totals(&self) -> Totals {
+        self.totals
+    }
+}
+
+pub fn process_bytes(bytes: impl IntoIterator<Item = u8>) -> Totals {
+    let mut processor = Processor::new();
+    for byte in bytes {
+        processor.feed(byte);
+    }
+    processor.totals()
+}
+
+/// Process a stream as it is read, e.g. from a file far bigger than the memory.
+pub fn process_reader(reader: impl Read) -> io::Result<Totals> {
+    let mut processor = Processor::new();
+    for byte in BufReader::new(reader).bytes() {
+        processor.feed(byte?);
+    }
+    Ok(processor.totals())
+}
+
+/// A problem in a stream, at a byte offset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StreamError {
+    UnclosedGroup(usize),       // the `{` of a group never closed
+    StrayCloser(usize),         // a `}` without a group to close
+    UnterminatedGarbage(usize), // the `<` of garbage never closed
+    DanglingCancel(usize),      // a `!` at the end of the stream
+}
+
+impl StreamError {
+    pub fn offset(self) -> usize {
+        match self {
+            StreamError::UnclosedGroup(offset)
+            | StreamError::StrayCloser(offset)
+            | StreamError::UnterminatedGarbage(offset)
+            | StreamError::DanglingCancel(offset) => offset,
+        }
+    }
 
-named!(
-    stream_parser<CompleteStr, Vec<Char>>,
-    many0!(take1)
-);
+    fn to_line_error(self) -> LineError {
+        match self {
+            StreamError::UnclosedGroup(offset) => LineError::new(offset, "a group closed by `}`"),
+            StreamError::StrayCloser(offset) => LineError::new(offset, "a group to close"),
+            StreamError::UnterminatedGarbage(offset) => {
+                LineError::new(offset, "garbage closed by `>`")
+            }
+            StreamError::DanglingCancel(offset) => {
+                LineError::new(offset + 1, "a character to cancel")
+            }
+        }
+    }
+}
 
-pub fn answer1(input: &str) -> u64 {
-    let stream = parse_input(&input);
-    scoring(&stream, 0, 0, false)
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::UnclosedGroup(offset) => write!(f, "unclosed group at {}", offset),
+            StreamError::StrayCloser(offset) => write!(f, "stray `}}` at {}", offset),
+            StreamError::UnterminatedGarbage(offset) => {
+                write!(f, "unterminated garbage at {}", offset)
+            }
+            StreamError::DanglingCancel(offset) => write!(f, "dangling `!` at {}", offset),
+        }
+    }
 }
 
-pub fn answer2(input: &str) -> u64 {
-    let stream = parse_input(&input);
-    count_garbage(&stream, 0, false)
+/// A processor keeping where the problems of a stream are.
+#[derive(Debug, Default)]
+pub struct Validator {
+    processor: Processor,
+    offset: usize,
+    open: Vec<usize>, // the offsets of the groups not closed yet
+    garbage: Option<usize>,
+    cancel: Option<usize>,
+    errors: Vec<StreamError>,
 }
 
-fn parse_input(input: &str) -> Vec<Char> {
-    stream_parser(CompleteStr(input)).unwrap().1
+impl Validator {
+    pub fn new() -> Self {
+        Validator::default()
+    }
+
+    pub fn feed(&mut self, byte: u8) {
+        match self.processor.feed(byte) {
+            Token::Open => self.open.push(self.offset),
+            Token::Close => {
+                self.open.pop();
+            }
+            Token::Stray => self.errors.push(StreamError::StrayCloser(self.offset)),
+            Token::GarbageStart => self.garbage = Some(self.offset),
+            Token::GarbageEnd => self.garbage = None,
+            Token::Cancel => self.cancel = Some(self.offset),
+            Token::Cancelled => self.cancel = None,
+            Token::Garbage | Token::Other => {}
+        }
+        self.offset += 1;
+    }
+
+    /// The totals, the groups left open being closed at the end of the stream,
+    /// and the problems found, by offset.
+    pub fn finish(mut self) -> (Totals, Vec<StreamError>) {
+        let mut totals = self.processor.totals();
+        let depth = self.open.len() as u64;
+        totals.score += depth * (depth + 1) / 2;
+
+        let errors = &mut self.errors;
+        errors.extend(self.open.iter().map(|o| StreamError::UnclosedGroup(*o)));
+        errors.extend(self.garbage.map(StreamError::UnterminatedGarbage));
+        errors.extend(self.cancel.map(StreamError::DanglingCancel));
+        errors.sort_by_key(|e| e.offset());
+        (totals, self.errors)
+    }
 }
 
-fn scoring(stream: &[Char], depth: u64, score: u64, is_garbage: bool) -> u64 {
-    use self::Char::*;
-    let first_char = stream.get(0);
+/// The totals of a well-formed stream, or all its problems.
+pub fn validate(bytes: impl IntoIterator<Item = u8>) -> Result<Totals, Vec<StreamError>> {
+    match recover(bytes) {
+        (totals, errors) if errors.is_empty() => Ok(totals),
+        (_, errors) => Err(errors),
+    }
+}
 
-    match (first_char, is_garbage) {
-        (Some(OpenStream), false) => scoring(&stream[1..], depth + 1, score, is_garbage),
-        (Some(CloseStream), false) => scoring(&stream[1..], depth - 1, score + depth, is_garbage),
-        (Some(OpenGarbage), false) => scoring(&stream[1..], depth, score, true),
-        (Some(CloseGarbage), true) => scoring(&stream[1..], depth, score, false),
-        (Some(Negate), _) => scoring(&stream[2..], depth, score, is_garbage),
-        (Some(Other), _) => scoring(&stream[1..], depth, score, is_garbage),
-        (None, _) => score,
-        (_, _) => scoring(&stream[1..], depth, score, is_garbage), //skip
+/// The best totals of a stream despite its problems, and these problems:
+/// stray closers are ignored, and what is left open is closed at the end.
+pub fn recover(bytes: impl IntoIterator<Item = u8>) -> (Totals, Vec<StreamError>) {
+    let mut validator = Validator::new();
+    for byte in bytes {
+        validator.feed(byte);
     }
+    validator.finish()
+}
+
+/// Some garbage, `<` and `>` included in its span.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Garbage {
+    pub span: Range<usize>,
+    pub raw: String,  // between `<` and `>`, as written
+    pub text: String, // without the `!` and the characters they cancel
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Group(usize), // the index of the group in the tree
+    Garbage(Garbage),
+}
+
+/// A group, `{` and `}` included in its span.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+    pub span: Range<usize>,
+    pub depth: usize, // 1 for the outermost groups
+    pub children: Vec<Node>,
 }
 
-fn count_garbage(stream: &[Char], count: u64, is_garbage: bool) -> u64 {
-    use self::Char::*;
-    let first_char = stream.get(0);
+/// The groups of a stream, kept in a flat list so that deep streams need no recursion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tree {
+    pub groups: Vec<Group>, // in the order they are opened
+    pub top: Vec<Node>,     // the nodes outside of any group
+}
 
-    match (first_char, is_garbage) {
-        (Some(OpenGarbage), false) => count_garbage(&stream[1..], count, true),
-        (Some(CloseGarbage), true) => count_garbage(&stream[1..], count, false),
-        (Some(Negate), _) => count_garbage(&stream[2..], count, is_garbage),
-        (None, _) => count,
-        (_, true) => count_garbage(&stream[1..], count + 1, is_garbage), //skip
-        (_, false) => count_garbage(&stream[1..], count, is_garbage),    //skip
+impl Garbage {
+    // the garbage opened at `start`, and closed at `end` unless it is the end of the stream
+    fn new(stream: &str, start: usize, end: usize, text: &[u8]) -> Self {
+        Garbage {
+            span: start..(end + 1).min(stream.len()),
+            raw: stream[start + 1..end].to_string(),
+            text: String::from_utf8_lossy(text).into_owned(),
+        }
     }
 }
 
+impl Tree {
+    /// An unclosed group or garbage goes until the end of the stream.
+    pub fn new(stream: &str) -> Self {
+        let mut tree = Tree {
+            groups: vec![],
+            top: vec![],
+        };
+        let mut open: Vec<usize> = vec![];
+        let mut garbage: Option<(usize, Vec<u8>)> = None;
+
+        let mut processor = Processor::new();
+        for (i, byte) in stream.bytes().enumerate() {
+            match processor.feed(byte) {
+                Token::Open => {
+                    let group = tree.groups.len();
+                    tree.groups.push(Group {
+                        span: i..stream.len(),
+                        depth: open.len() + 1,
+                        children: vec![],
+                    });
+                    tree.attach(open.last(), Node::Group(group));
+                    open.push(group);
+                }
+                Token::Close => {
+                    let group = open.pop().unwrap();
+                    tree.groups[group].span.end = i + 1;
+                }
+                Token::GarbageStart => garbage = Some((i, vec![])),
+                Token::Garbage => garbage.as_mut().unwrap().1.push(byte),
+                Token::GarbageEnd => {
+                    let (start, text) = garbage.take().unwrap();
+                    let node = Node::Garbage(Garbage::new(stream, start, i, &text));
+                    tree.attach(open.last(), node);
+                }
+                Token::Stray | Token::Cancel | Token::Cancelled | Token::Other => {}
+            }
+        }
+        if let Some((start, text)) = garbage {
+            let node = Node::Garbage(Garbage::new(stream, start, stream.len(), &text));
+            tree.attach(open.last(), node);
+        }
+        tree
+    }
+
+    fn attach(&mut self, parent: Option<&usize>, node: Node) {
+        match parent {
+            Some(parent) => self.groups[*parent].children.push(node),
+            None => self.top.push(node),
+        }
+    }
+
+    /// The first of the most nested groups.
+    pub fn deepest(&self) -> Option<&Group> {
+        self.groups.iter().rev().max_by_key(|g| g.depth)
+    }
+
+    pub fn at_depth(&self, depth: usize) -> impl Iterator<Item = &Group> {
+        self.groups.iter().filter(move |g| g.depth == depth)
+    }
+
+    pub fn garbage(&self) -> impl Iterator<Item = &Garbage> {
+        self.top
+            .iter()
+            .chain(self.groups.iter().flat_map(|g| g.children.iter()))
+            .filter_map(|node| match node {
+                Node::Garbage(garbage) => Some(garbage),
+                Node::Group(_) => None,
+            })
+    }
+
+    /// The first of the garbage with the most characters left once cancelled.
+    pub fn largest_garbage(&self) -> Option<&Garbage> {
+        self.garbage()
+            .max_by_key(|g| (g.text.len(), Reverse(g.span.start)))
+    }
+}
+
+// one node per line, indented by depth:
+//
+//     group 0..13
+//       garbage 1..6 `a!>b` -> `ab`
+//       group 7..12
+impl fmt::Display for Tree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut stack: Vec<(usize, &Node)> = self.top.iter().rev().map(|n| (0, n)).collect();
+        while let Some((indent, node)) = stack.pop() {
+            write!(f, "{:1$}", "", indent * 2)?;
+            match node {
+                Node::Group(index) => {
+                    let group = &self.groups[*index];
+                    writeln!(f, "group {}..{}", group.span.start, group.span.end)?;
+                    stack.extend(group.children.iter().rev().map(|n| (indent + 1, n)));
+                }
+                Node::Garbage(g) => writeln!(
+                    f,
+                    "garbage {}..{} `{}` -> `{}`",
+                    g.span.start, g.span.end, g.raw, g.text
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Solver for Day09 {
+    const DAY: u32 = 9;
+    const TITLE: &'static str = "Day 09: Stream Processing";
+
+    type Input = String;
+    type Output1 = u64;
+    type Output2 = u64;
+
+    fn parse_input(input: &str) -> Result<String, ParseError> {
+        common::parse_line(Self::DAY, input, |line| match validate(line.bytes()) {
+            Ok(_) => Ok(line.to_string()),
+            Err(errors) => Err(errors[0].to_line_error()),
+        })
+    }
+
+    fn answer1(stream: &String) -> u64 {
+        process_bytes(stream.bytes()).score
+    }
+
+    fn answer2(stream: &String) -> u64 {
+        process_bytes(stream.bytes()).garbage
+    }
+}
+
+#[test]
+fn test_answer1() {
+    assert_eq!(Day09::run1("{}").unwrap(), 1);
+    assert_eq!(Day09::run1("{{{}}}").unwrap(), 6);
+    assert_eq!(Day09::run1("{{},{}}").unwrap(), 5);
+    assert_eq!(Day09::run1("{{{},{},{{}}}}").unwrap(), 16);
+    assert_eq!(Day09::run1("{<a>,<a>,<a>,<a>}").unwrap(), 1);
+    assert_eq!(Day09::run1("{{<ab>},{<ab>},{<ab>},{<ab>}}").unwrap(), 9);
+    assert_eq!(Day09::run1("{{<!!>},{<!!>},{<!!>},{<!!>}}").unwrap(), 9);
+    assert_eq!(Day09::run1("{{<a!>},{<a!>},{<a!>},{<ab>}}").unwrap(), 3);
+}
+
+#[test]
+fn test_answer2() {
+    assert_eq!(Day09::run2("<>").unwrap(), 0);
+    assert_eq!(Day09::run2("<random characters>").unwrap(), 17);
+    assert_eq!(Day09::run2("<<<<>").unwrap(), 3);
+    assert_eq!(Day09::run2("<{!>}>").unwrap(), 2);
+    assert_eq!(Day09::run2("<!!>").unwrap(), 0);
+    assert_eq!(Day09::run2("<!!!>>").unwrap(), 0);
+    assert_eq!(Day09::run2("<{oi!a,<{i<a>").unwrap(), 9);
+}
+
 #[test]
-fn parse_stream() {
-    use self::Char::*;
+fn test_process_reader() {
+    // the first garbage is `<!>},{<!!>`, with 4 characters
+    let stream = "{{<!>},{<!!>},{<a>},{<ab>}}";
     assert_eq!(
-        stream_parser(CompleteStr::from("<{oi!}a,<{i<a>")),
-        Ok((
-            CompleteStr::from(""),
-            vec![
-                OpenGarbage,
-                OpenStream,
-                Other,
-                Other,
-                Negate,
-                CloseStream,
-                Other,
-                Other,
-                OpenGarbage,
-                OpenStream,
-                Other,
-                OpenGarbage,
-                Other,
-                CloseGarbage
-            ]
-        ))
+        process_reader(stream.as_bytes()).unwrap(),
+        Totals {
+            score: 7,
+            garbage: 7
+        }
     );
+
+    // deep enough to overflow the stack of a recursive parser
+    let deep = "{".repeat(1_000_000) + &"}".repeat(1_000_000);
+    let totals = process_reader(deep.as_bytes()).unwrap();
+    assert_eq!(totals.score, 1_000_000 * 1_000_001 / 2);
 }
 
 #[test]
-fn test_answer1() {
-    assert_eq!(answer1("{}"), 1);
-    assert_eq!(answer1("{{{}}}"), 6);
-    assert_eq!(answer1("{{},{}}"), 5);
-    assert_eq!(answer1("{{{},{},{{}}}}"), 16);
-    assert_eq!(answer1("{<a>,<a>,<a>,<a>}"), 1);
-    assert_eq!(answer1("{{<ab>},{<ab>},{<ab>},{<ab>}}"), 9);
-    assert_eq!(answer1("{{<!!>},{<!!>},{<!!>},{<!!>}}"), 9);
-    assert_eq!(answer1("{{<a!>},{<a!>},{<a!>},{<ab>}}"), 3);
+fn test_tree() {
+    let tree = Tree::new("{{<a!>b>},{{}},<x>}");
+    assert_eq!(
+        tree.to_string(),
+        "group 0..19
+  group 1..9
+    garbage 2..8 `a!>b` -> `ab`
+  group 10..14
+    group 11..13
+  garbage 15..18 `x` -> `x`
+"
+    );
+    assert_eq!(tree.deepest().map(|g| g.span.clone()), Some(11..13));
+    assert_eq!(tree.at_depth(2).count(), 2);
+    assert_eq!(tree.largest_garbage().map(|g| g.span.clone()), Some(2..8));
+    assert_eq!(
+        tree.groups[1].children,
+        vec![Node::Garbage(Garbage {
+            span: 2..8,
+            raw: "a!>b".to_string(),
+            text: "ab".to_string(),
+        })]
+    );
+
+    // unclosed
+    let tree = Tree::new("{{}<ab");
+    assert_eq!(
+        tree.to_string(),
+        "group 0..6\n  group 1..3\n  garbage 3..6 `ab` -> `ab`\n"
+    );
 }
 
 #[test]
-fn test_answer2() {
-    assert_eq!(answer2("<>"), 0);
-    assert_eq!(answer2("<random characters>"), 17);
-    assert_eq!(answer2("<<<<>"), 3);
-    assert_eq!(answer2("<{!>}>"), 2);
-    assert_eq!(answer2("<!!>"), 0);
-    assert_eq!(answer2("<!!!>>"), 0);
-    assert_eq!(answer2("<{oi!a,<{i<a>"), 9);
+fn test_validate() {
+    use self::StreamError::*;
+
+    assert_eq!(
+        validate("{{<ab>},{}}".bytes()),
+        Ok(Totals {
+            score: 5,
+            garbage: 2
+        })
+    );
+    assert_eq!(validate("{{}".bytes()), Err(vec![UnclosedGroup(0)]));
+    assert_eq!(
+        validate("}{".bytes()),
+        Err(vec![StrayCloser(0), UnclosedGroup(1)])
+    );
+    assert_eq!(
+        validate("{<a!".bytes()),
+        Err(vec![
+            UnclosedGroup(0),
+            UnterminatedGarbage(1),
+            DanglingCancel(3)
+        ])
+    );
+}
+
+#[test]
+fn test_recover() {
+    // the outer group is closed at the end
+    assert_eq!(
+        recover("{{}".bytes()),
+        (
+            Totals {
+                score: 3,
+                garbage: 0
+            },
+            vec![StreamError::UnclosedGroup(0)]
+        )
+    );
+    assert_eq!(recover("}{}}".bytes()).0.score, 1);
+    assert_eq!(
+        recover("{<ab".bytes()),
+        (
+            Totals {
+                score: 1,
+                garbage: 2
+            },
+            vec![
+                StreamError::UnclosedGroup(0),
+                StreamError::UnterminatedGarbage(1)
+            ]
+        )
+    );
+}
+
+#[test]
+fn test_parse_input() {
+    assert_eq!(
+        Day09::parse_input("{{},{}").unwrap_err().to_string(),
+        "day 09, line 1, column 1: expected a group closed by `}`, found `{{}`"
+    );
+    assert_eq!(
+        Day09::parse_input("{}}").unwrap_err().to_string(),
+        "day 09, line 1, column 3: expected a group to close, found `}`"
+    );
+    assert_eq!(
+        Day09::parse_input("{<a!").unwrap_err().to_string(),
+        "day 09, line 1, column 1: expected a group closed by `}`, found `{<a!`"
+    );
+    assert_eq!(
+        Day09::parse_input("<a!").unwrap_err().to_string(),
+        "day 09, line 1, column 1: expected garbage closed by `>`, found `<a!`"
+    );
 }
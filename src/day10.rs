@@ -1,13 +1,17 @@
+use crate::common;
+use crate::error::LineError;
+use crate::{ParseError, Solver};
 use nom::types::CompleteStr;
 use nom::{map_res, named, separated_nonempty_list, tag};
+use std::fmt;
+use std::hash::{BuildHasherDefault, Hasher};
+use std::thread;
 
-pub fn title() -> &'static str {
-    "Day 10: Knot Hash"
-}
+pub struct Day10;
 
 named!(
     usize_parser<CompleteStr, usize>,
-    map_res!(nom::digit, |CompleteStr(s)| usize::from_str_radix(s, 10))
+    map_res!(nom::digit, |CompleteStr(s)| s.parse::<usize>())
 );
 
 named!(
@@ -15,155 +19,372 @@ named!(
     separated_nonempty_list!(tag!(","), usize_parser)
 );
 
+// part 1 reads the lengths as a list of numbers, part 2 hashes their text
 #[derive(Debug, PartialEq)]
+pub struct Knot {
+    size: usize,
+    lengths: Vec<usize>,
+    text: String,
+}
+
+// the marks live in the first `size` bytes of a fixed ring, a size of 256 being
+// the only one that fills it
+#[derive(Debug, Clone, PartialEq)]
 struct Hash {
-    list: Vec<usize>,
+    ring: [u8; 256],
+    size: usize,
     current_position: usize,
     skip_size: usize, // increment by 1 for everty .next()
 }
 
 impl Hash {
-    fn next(&mut self, length: usize) {
-        let size = self.list.len();
+    fn new(size: usize) -> Self {
+        assert!(size <= 256, "a ring has at most 256 marks");
+        let mut ring = [0; 256];
+        for (i, mark) in ring.iter_mut().enumerate() {
+            *mark = i as u8;
+        }
+        Hash {
+            ring,
+            size,
+            current_position: 0,
+            skip_size: 0,
+        }
+    }
 
-        // find the range of indices to reverse
-        let range: Vec<usize> = (self.current_position..self.current_position + length)
-            .map(|idx| idx % size)
-            .collect();
-        let reverse_range = range.clone().into_iter().rev();
+    fn marks(&self) -> &[u8] {
+        &self.ring[..self.size]
+    }
 
-        let mut new_list = self.list.clone();
-        for (idx, idx_rev) in range.into_iter().zip(reverse_range) {
-            new_list[idx] = self.list[idx_rev];
+    // reverse the circular segment in place, swapping its ends towards the middle
+    fn next(&mut self, length: usize) {
+        let size = self.size;
+        let (mut i, mut j) = (self.current_position, self.current_position + length);
+        while j > i + 1 {
+            j -= 1;
+            self.ring.swap(i % size, j % size);
+            i += 1;
         }
 
-        // increment and clean-up
         self.current_position = (self.current_position + length + self.skip_size) % size;
-        self.list = new_list;
         self.skip_size += 1;
     }
 
-    fn dense_hash(&self) -> String {
-        let dense_size = self.list.len() / 16;
-
-        (0..dense_size)
-            .map(|i| {
-                (0..16)
-                    .map(|ii| i * 16 + ii)
-                    .fold(0 as usize, |acc, index| acc ^ self.list[index])
-            })
-            .map(|c| format!("{:01$x}", c, 2))
-            .collect()
+    // xor the ring by blocks, one byte per block
+    fn dense_hash(&self) -> [u8; 16] {
+        let block = self.size / 16;
+        let mut digest = [0; 16];
+        for (i, byte) in digest.iter_mut().enumerate() {
+            *byte = self.ring[i * block..(i + 1) * block]
+                .iter()
+                .fold(0, |acc, n| acc ^ n);
+        }
+        digest
     }
 }
 
-pub fn answer1(size: usize, input: &str) -> usize {
-    let lengths = parse_input(&input);
+/// The 16 bytes of a knot hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Digest(pub [u8; 16]);
 
-    let mut hash = Hash {
-        list: (0..size).collect(),
-        current_position: 0,
-        skip_size: 0,
-    };
+impl Digest {
+    pub fn bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+
+    /// The 32 lowercase hexadecimal digits.
+    pub fn hex(&self) -> String {
+        self.0.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// The 128 binary digits.
+    pub fn binary(&self) -> String {
+        self.0.iter().map(|b| format!("{:08b}", b)).collect()
+    }
 
-    for length in lengths {
-        hash.next(length);
+    /// The 128 bits, most significant first.
+    pub fn bits(&self) -> impl Iterator<Item = bool> + '_ {
+        self.0
+            .iter()
+            .flat_map(|b| (0..8).rev().map(move |i| b & (1 << i) != 0))
     }
 
-    hash.list[0] * hash.list[1]
+    pub fn count_ones(&self) -> u32 {
+        self.0.iter().map(|b| b.count_ones()).sum()
+    }
 }
 
-pub fn hash(input: &str) -> String {
-    let mut lengths = input
-        .trim()
-        .chars()
-        .map(|c| c as u8)
-        .map(|c| c as usize)
-        .collect::<Vec<usize>>();
-    lengths.extend(&[17, 31, 73, 47, 23]);
-
-    let mut hash = Hash {
-        list: (0..256).collect(),
-        current_position: 0,
-        skip_size: 0,
-    };
-
-    for _ in 0..64 {
-        for length in lengths.clone() {
-            hash.next(length);
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.hex())
+    }
+}
+
+/// A knot hash of everything written to it, e.g. to key a `HashMap` through
+/// `BuildKnotHasher`, or to checksum some bytes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KnotHasher {
+    size: usize,
+    rounds: usize,
+    suffix: Vec<u8>,
+    lengths: Vec<u8>,
+}
+
+pub type BuildKnotHasher = BuildHasherDefault<KnotHasher>;
+
+impl Default for KnotHasher {
+    fn default() -> Self {
+        KnotHasher {
+            size: 256,
+            rounds: 64,
+            suffix: vec![17, 31, 73, 47, 23],
+            lengths: vec![],
+        }
+    }
+}
+
+impl KnotHasher {
+    pub fn new() -> Self {
+        KnotHasher::default()
+    }
+
+    /// The number of marks on the ring, a multiple of 16 up to 256. The lengths
+    /// longer than the ring are taken modulo its size + 1.
+    pub fn ring_size(mut self, size: usize) -> Self {
+        assert!(
+            size > 0 && size <= 256 && size.is_multiple_of(16),
+            "the ring size must be a multiple of 16 up to 256"
+        );
+        self.size = size;
+        self
+    }
+
+    pub fn rounds(mut self, rounds: usize) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    /// The lengths added after the written bytes.
+    pub fn suffix(mut self, suffix: &[u8]) -> Self {
+        self.suffix = suffix.to_vec();
+        self
+    }
+
+    pub fn digest(&self) -> Digest {
+        let mut hash = Hash::new(self.size);
+
+        let lengths = self.lengths.iter().chain(&self.suffix);
+        for _ in 0..self.rounds {
+            for length in lengths.clone() {
+                hash.next(*length as usize % (self.size + 1));
+            }
         }
+
+        Digest(hash.dense_hash())
+    }
+}
+
+impl Hasher for KnotHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.lengths.extend_from_slice(bytes);
     }
 
-    hash.dense_hash()
+    // the first 8 bytes of the digest
+    fn finish(&self) -> u64 {
+        let digest = self.digest();
+        digest.0[..8]
+            .iter()
+            .fold(0, |acc, b| (acc << 8) | u64::from(*b))
+    }
+}
+
+/// The knot hash of some bytes, with the parameters of the puzzle.
+pub fn digest(bytes: &[u8]) -> Digest {
+    let mut hasher = KnotHasher::new();
+    hasher.write(bytes);
+    hasher.digest()
 }
 
-pub fn answer2(input: &str) -> String {
-    hash(input)
+/// The knot hashes of many inputs, in their order, spread over one thread per CPU.
+pub fn digests<T: AsRef<[u8]> + Sync>(inputs: &[T]) -> Vec<Digest> {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk = inputs.len().div_ceil(threads).max(1);
+    let mut digests = vec![Digest([0; 16]); inputs.len()];
+    thread::scope(|scope| {
+        for (inputs, digests) in inputs.chunks(chunk).zip(digests.chunks_mut(chunk)) {
+            scope.spawn(move || {
+                for (input, d) in inputs.iter().zip(digests) {
+                    *d = digest(input.as_ref());
+                }
+            });
+        }
+    });
+    digests
 }
 
-fn parse_input(input: &str) -> Vec<usize> {
-    list_parser(CompleteStr(input)).unwrap().1
+pub fn hash(input: &str) -> String {
+    digest(input.trim().as_bytes()).hex()
+}
+
+impl Solver for Day10 {
+    const DAY: u32 = 10;
+    const TITLE: &'static str = "Day 10: Knot Hash";
+
+    type Input = Knot;
+    type Output1 = usize;
+    type Output2 = String;
+
+    // the examples start with a smaller ring, e.g. `size: 5`
+    fn parse_input(input: &str) -> Result<Knot, ParseError> {
+        let size = |v: &str| v.parse().ok().filter(|s| (2..=256).contains(s));
+        common::parse_with_parameter(
+            Self::DAY,
+            input,
+            "size",
+            size,
+            "a size from 2 to 256",
+            |size, input| {
+                let size = size.unwrap_or(256);
+                let lengths = common::parse_line(Self::DAY, input, |line| {
+                    let lengths = common::parse_nom(line, list_parser, "a list of lengths")?;
+                    match line.split(',').zip(&lengths).find(|(_, l)| **l > size) {
+                        Some((l, _)) => Err(LineError::new(
+                            common::offset(line, l),
+                            "a length up to the size of the ring",
+                        )),
+                        None => Ok(lengths),
+                    }
+                })?;
+
+                Ok(Knot {
+                    size,
+                    lengths,
+                    text: input.trim().to_string(),
+                })
+            },
+        )
+    }
+
+    fn answer1(knot: &Knot) -> usize {
+        let mut hash = Hash::new(knot.size);
+
+        for length in &knot.lengths {
+            hash.next(*length);
+        }
+
+        let marks = hash.marks();
+        usize::from(marks[0]) * usize::from(marks[1])
+    }
+
+    fn answer2(knot: &Knot) -> String {
+        hash(&knot.text)
+    }
 }
 
 #[test]
 fn test_parse_input() {
-    assert_eq!(parse_input(&"3,4,1,5".to_string()), vec![3, 4, 1, 5]);
+    let knot = Day10::parse_input("3,4,1,5\n").unwrap();
+    assert_eq!(knot.lengths, vec![3, 4, 1, 5]);
+    assert_eq!(knot.text, "3,4,1,5");
+
+    let knot = Day10::parse_input("size: 5\n3,4,1,5\n").unwrap();
+    assert_eq!((knot.size, knot.text.as_str()), (5, "3,4,1,5"));
+    assert_eq!(
+        Day10::parse_input("size: 5\n3,4,6")
+            .unwrap_err()
+            .to_string(),
+        "day 10, line 2, column 5: expected a length up to the size of the ring, found `6`"
+    );
+    assert!(Day10::parse_input("size: 1\n1").is_err());
+    assert!(Day10::parse_input("size: 257\n1").is_err());
 }
 
 #[test]
 fn test_next() {
-    let mut init = Hash {
-        list: vec![0, 1, 2, 3, 4],
-        current_position: 0,
-        skip_size: 0,
-    };
-    let expected = Hash {
-        list: vec![2, 1, 0, 3, 4],
-        current_position: 3,
-        skip_size: 1,
-    };
-
-    init.next(3);
-    assert_eq!(init, expected);
-
-    let expected = Hash {
-        list: vec![4, 3, 0, 1, 2],
-        current_position: 3,
-        skip_size: 2,
-    };
-    init.next(4);
-    assert_eq!(init, expected);
-
-    let expected = Hash {
-        list: vec![4, 3, 0, 1, 2],
-        current_position: 1,
-        skip_size: 3,
-    };
-    init.next(1);
-    assert_eq!(init, expected);
+    let mut hash = Hash::new(5);
+
+    hash.next(3);
+    assert_eq!(hash.marks(), &[2, 1, 0, 3, 4]);
+    assert_eq!((hash.current_position, hash.skip_size), (3, 1));
+
+    hash.next(4);
+    assert_eq!(hash.marks(), &[4, 3, 0, 1, 2]);
+    assert_eq!((hash.current_position, hash.skip_size), (3, 2));
+
+    hash.next(1);
+    assert_eq!(hash.marks(), &[4, 3, 0, 1, 2]);
+    assert_eq!((hash.current_position, hash.skip_size), (1, 3));
+
+    // the whole ring, from the middle
+    hash.next(5);
+    assert_eq!(hash.marks(), &[3, 4, 2, 1, 0]);
+    assert_eq!(&hash.ring[5..8], &[5, 6, 7]);
 }
 
 #[test]
 fn test_answer1() {
-    assert_eq!(answer1(5, &"3,4,1,5".to_string()), 3 * 4);
+    assert_eq!(Day10::run1("size: 5\n3,4,1,5").unwrap(), 3 * 4);
 }
 
 #[test]
 fn test_answer2() {
+    assert_eq!(hash(""), "a2582a3a0e66e6e86e3812dcb672a272".to_string());
     assert_eq!(
-        answer2(&"".to_string()),
-        "a2582a3a0e66e6e86e3812dcb672a272".to_string()
-    );
-    assert_eq!(
-        answer2(&"AoC 2017".to_string()),
+        hash("AoC 2017"),
         "33efeb34ea91902bb2f59c9920caa6cd".to_string()
     );
     assert_eq!(
-        answer2(&"1,2,3".to_string()),
+        Day10::run2("1,2,3").unwrap(),
         "3efbe78a8d82f29979031a4aa0b16a9d".to_string()
     );
     assert_eq!(
-        answer2(&"1,2,4".to_string()),
+        Day10::run2("1,2,4").unwrap(),
         "63960835bcdc130f0b66d7ff4f6a5a8e".to_string()
     );
 }
+
+#[test]
+fn test_knot_hasher() {
+    let mut hasher = KnotHasher::new();
+    hasher.write(b"AoC ");
+    hasher.write(b"2017");
+    let digest = hasher.digest();
+    assert_eq!(digest, self::digest(b"AoC 2017"));
+    assert_eq!(digest.to_string(), "33efeb34ea91902bb2f59c9920caa6cd");
+    assert_eq!(&digest.binary()[..12], "001100111110");
+    assert_eq!(digest.bits().count(), 128);
+    assert_eq!(
+        digest.bits().filter(|b| *b).count() as u32,
+        digest.count_ones()
+    );
+    assert_eq!(hasher.finish(), 0x33ef_eb34_ea91_902b);
+
+    // a single round without suffix on 16 marks, a ring of 16 blocks of one mark
+    let mut hasher = KnotHasher::new().ring_size(16).rounds(1).suffix(&[]);
+    hasher.write(&[3, 4, 1, 5]);
+    let mut hash = Hash::new(16);
+    for length in &[3, 4, 1, 5] {
+        hash.next(*length);
+    }
+    assert_eq!(hasher.digest().bytes(), hash.marks());
+}
+
+#[test]
+fn test_hash_map() {
+    use std::collections::HashMap;
+
+    let mut map: HashMap<&str, usize, BuildKnotHasher> = HashMap::default();
+    map.insert("flqrgnkx", 1);
+    map.insert("AoC 2017", 2);
+    assert_eq!(map.get("flqrgnkx"), Some(&1));
+    assert_eq!(map.get("AoC 2017"), Some(&2));
+    assert_eq!(map.get("1,2,3"), None);
+}
+
+#[test]
+fn test_digests() {
+    let inputs: Vec<String> = (0..10).map(|i| format!("flqrgnkx-{}", i)).collect();
+    let expected: Vec<Digest> = inputs.iter().map(|i| digest(i.as_bytes())).collect();
+    assert_eq!(digests(&inputs), expected);
+    assert_eq!(digests::<&str>(&[]), vec![]);
+}
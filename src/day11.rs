@@ -1,11 +1,11 @@
+use crate::common;
+use crate::{ParseError, Solver};
 use nom::types::CompleteStr;
 use nom::{map_res, named, separated_nonempty_list, tag};
 use std::cmp::max;
 use std::str::FromStr;
 
-pub fn title() -> &'static str {
-    "Day 11: Hex Ed"
-}
+pub struct Day11;
 
 named!(
     hex_parser<CompleteStr, Hex>,
@@ -17,8 +17,8 @@ named!(
     separated_nonempty_list!(tag!(","), hex_parser)
 );
 
-#[derive(Debug, PartialEq)]
-enum Hex {
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Hex {
     N,
     NE,
     NW,
@@ -86,46 +86,53 @@ impl CubeCoords {
     }
 }
 
-pub fn answer1(input: &str) -> i32 {
-    let hexes = parse_input(input);
+impl Solver for Day11 {
+    const DAY: u32 = 11;
+    const TITLE: &'static str = "Day 11: Hex Ed";
 
-    let mut cur_coords = CubeCoords { x: 0, y: 0, z: 0 };
-    for hex in hexes {
-        cur_coords.move_hex(hex);
-    }
+    type Input = Vec<Hex>;
+    type Output1 = i32;
+    type Output2 = i32;
 
-    cur_coords.distance_from_center()
-}
+    fn parse_input(input: &str) -> Result<Vec<Hex>, ParseError> {
+        common::parse_line(Self::DAY, input, |line| {
+            common::parse_nom(line, hexes_parser, "a direction (n, ne, se, s, sw, nw)")
+        })
+    }
 
-pub fn answer2(input: &str) -> i32 {
-    let hexes = parse_input(input);
+    fn answer1(hexes: &Vec<Hex>) -> i32 {
+        let mut cur_coords = CubeCoords { x: 0, y: 0, z: 0 };
+        for hex in hexes {
+            cur_coords.move_hex(*hex);
+        }
 
-    let mut cur_coords = CubeCoords { x: 0, y: 0, z: 0 };
-    let mut max_distance = 0;
-    for hex in hexes {
-        cur_coords.move_hex(hex);
-        max_distance = max(cur_coords.distance_from_center(), max_distance);
+        cur_coords.distance_from_center()
     }
 
-    max_distance
-}
+    fn answer2(hexes: &Vec<Hex>) -> i32 {
+        let mut cur_coords = CubeCoords { x: 0, y: 0, z: 0 };
+        let mut max_distance = 0;
+        for hex in hexes {
+            cur_coords.move_hex(*hex);
+            max_distance = max(cur_coords.distance_from_center(), max_distance);
+        }
 
-fn parse_input(input: &str) -> Vec<Hex> {
-    hexes_parser(CompleteStr(input)).unwrap().1
+        max_distance
+    }
 }
 
 #[test]
 fn test_parse_input() {
     assert_eq!(
-        parse_input(&"se,sw,s,nw,ne,n".to_string()),
+        Day11::parse_input("se,sw,s,nw,ne,n").unwrap(),
         vec![Hex::SE, Hex::SW, Hex::S, Hex::NW, Hex::NE, Hex::N]
     );
 }
 
 #[test]
 fn test_answer1() {
-    assert_eq!(answer1(&"ne,ne,ne".to_string()), 3);
-    assert_eq!(answer1(&"ne,ne,sw,sw".to_string()), 0);
-    assert_eq!(answer1(&"ne,ne,s,s".to_string()), 2);
-    assert_eq!(answer1(&"se,sw,se,sw,sw".to_string()), 3);
+    assert_eq!(Day11::run1("ne,ne,ne").unwrap(), 3);
+    assert_eq!(Day11::run1("ne,ne,sw,sw").unwrap(), 0);
+    assert_eq!(Day11::run1("ne,ne,s,s").unwrap(), 2);
+    assert_eq!(Day11::run1("se,sw,se,sw,sw").unwrap(), 3);
 }
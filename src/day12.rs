@@ -1,14 +1,14 @@
+use crate::common;
+use crate::{ParseError, Solver};
 use nom::types::CompleteStr;
 use nom::{do_parse, map_res, named, separated_nonempty_list, tag};
 use std::collections::HashSet;
 
-pub fn title() -> &'static str {
-    "Day 12: Digital Plumber"
-}
+pub struct Day12;
 
 named!(
     id_parser<CompleteStr, u32>,
-    map_res!(nom::digit, |CompleteStr(s)| u32::from_str_radix(s, 10))
+    map_res!(nom::digit, |CompleteStr(s)| s.parse::<u32>())
 );
 
 named!(
@@ -22,24 +22,12 @@ named!(
         source: id_parser
             >> tag!(" <-> ")
             >> target: target_parser
-            >> (Pipe {
-                source: source,
-                target: target
-            })
+            >> (Pipe { source, target })
     )
 );
 
-fn parse_input(input: &str) -> Vec<Pipe> {
-    let lines = input.split('\n');
-
-    lines
-        .filter(|l| *l != "")
-        .map(|l| pipe_parser(CompleteStr(l)).unwrap().1)
-        .collect()
-}
-
 #[derive(Debug, PartialEq)]
-struct Pipe {
+pub struct Pipe {
     source: u32,
     target: Vec<u32>,
 }
@@ -55,31 +43,49 @@ fn neighbours(pipes: &[Pipe]) -> Vec<Vec<u32>> {
     neighbours
 }
 
-pub fn answer1(input: &str) -> usize {
-    let pipes = parse_input(&input);
-    let neighbours = neighbours(&pipes);
+impl Solver for Day12 {
+    const DAY: u32 = 12;
+    const TITLE: &'static str = "Day 12: Digital Plumber";
+
+    type Input = Vec<Pipe>;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn parse_input(input: &str) -> Result<Vec<Pipe>, ParseError> {
+        let pipes = common::parse_lines(Self::DAY, input, |l| {
+            common::parse_nom(l, pipe_parser, "`id <-> id, id, ...`")
+        })?;
+        // the first answer is about its group
+        if !pipes.iter().any(|pipe| pipe.source == 0) {
+            return Err(common::missing(Self::DAY, input, "program 0"));
+        }
+        Ok(pipes)
+    }
+
+    fn answer1(pipes: &Vec<Pipe>) -> usize {
+        let neighbours = neighbours(pipes);
 
-    // https://docs.rs/pathfinding/1.1.10/pathfinding/undirected/connected_components/fn.components.html
-    let groups = pathfinding::undirected::connected_components::components(&neighbours);
+        // https://docs.rs/pathfinding/1.1.10/pathfinding/undirected/connected_components/fn.components.html
+        let groups = pathfinding::undirected::connected_components::components(&neighbours);
 
-    // find the group which contains 0, and return it's size
-    for group in groups {
-        if group.contains(&0) {
-            return group.len();
+        // find the group which contains 0, and return it's size
+        for group in groups {
+            if group.contains(&0) {
+                return group.len();
+            }
         }
-    }
 
-    unreachable!();
-}
+        unreachable!();
+    }
 
-pub fn answer2(input: &str) -> usize {
-    let pipes = parse_input(&input);
-    let neighbours = neighbours(&pipes);
+    fn answer2(pipes: &Vec<Pipe>) -> usize {
+        let neighbours = neighbours(pipes);
 
-    // https://docs.rs/pathfinding/1.1.10/pathfinding/undirected/connected_components/fn.components.html
-    let groups = pathfinding::undirected::connected_components::components(&neighbours);
+        // https://docs.rs/pathfinding/1.1.10/pathfinding/undirected/connected_components/fn.components.html
+        let groups = pathfinding::undirected::connected_components::components(&neighbours);
 
-    groups.len()
+        groups.len()
+    }
 }
 
 #[test]
@@ -91,7 +97,7 @@ fn test_parse_input() {
     );
 
     assert_eq!(
-        parse_input(&input),
+        Day12::parse_input(&input).unwrap(),
         vec![
             Pipe {
                 source: 0,
@@ -103,6 +109,10 @@ fn test_parse_input() {
             }
         ]
     );
+    assert_eq!(
+        Day12::parse_input("\n1 <-> 1").unwrap_err().to_string(),
+        "day 12, line 2, column 1: expected program 0, found `1`"
+    );
 }
 
 #[test]
@@ -118,7 +128,7 @@ fn test_answer1() {
 6 <-> 4, 5"#,
     );
 
-    assert_eq!(answer1(&input), 6);
+    assert_eq!(Day12::run1(&input).unwrap(), 6);
 }
 
 #[test]
@@ -134,5 +144,5 @@ fn test_answer2() {
 6 <-> 4, 5"#,
     );
 
-    assert_eq!(answer2(&input), 2);
+    assert_eq!(Day12::run2(&input).unwrap(), 2);
 }
@@ -1,14 +1,15 @@
+use crate::common;
+use crate::error::LineError;
+use crate::{ParseError, Solver};
 use nom::types::CompleteStr;
 use nom::{do_parse, map_res, named, tag};
 use std::collections::HashMap;
 
-pub fn title() -> &'static str {
-    "Day 13: Packet Scanners"
-}
+pub struct Day13;
 
 named!(
     id_parser<CompleteStr, u32>,
-    map_res!(nom::digit, |CompleteStr(s)| u32::from_str_radix(s, 10))
+    map_res!(nom::digit, |CompleteStr(s)| s.parse::<u32>())
 );
 
 named!(
@@ -17,63 +18,78 @@ named!(
         layer: id_parser
             >> tag!(": ")
             >> depth: id_parser
-            >> (Firewall {
-                layer: layer,
-                depth: depth
-            })
+            >> (Firewall { layer, depth })
     )
 );
 
-fn parse_input(input: &str) -> Vec<Firewall> {
-    let lines = input.split('\n');
-
-    lines
-        .filter(|l| *l != "")
-        .map(|l| firewall_parser(CompleteStr(l)).unwrap().1)
-        .collect()
-}
-
 #[derive(Debug, PartialEq)]
-struct Firewall {
+pub struct Firewall {
     layer: u32,
     depth: u32,
 }
 
-pub fn answer1(input: &str) -> u32 {
-    let firewalls = parse_input(&input);
+impl Solver for Day13 {
+    const DAY: u32 = 13;
+    const TITLE: &'static str = "Day 13: Packet Scanners";
+
+    type Input = Vec<Firewall>;
+    type Output1 = u32;
+    type Output2 = u32;
+
+    fn parse_input(input: &str) -> Result<Vec<Firewall>, ParseError> {
+        common::parse_lines(Self::DAY, input, |l| {
+            let firewall = common::parse_nom(l, firewall_parser, "`layer: depth`")?;
+            // a scanner needs two positions to go back and forth
+            if firewall.depth < 2 {
+                let offset = l.find(": ").map_or(0, |i| i + 2);
+                return Err(LineError::new(offset, "a depth of at least 2"));
+            }
+            Ok(firewall)
+        })
+    }
 
-    let firewalls: HashMap<u32, u32> = firewalls.iter().map(|f| (f.layer, f.depth)).collect();
+    fn answer1(firewalls: &Vec<Firewall>) -> u32 {
+        let firewalls: HashMap<u32, u32> = firewalls.iter().map(|f| (f.layer, f.depth)).collect();
 
-    let mut severity = 0;
+        let mut severity = 0;
 
-    for (layer, depth) in firewalls.iter() {
-        let scanner_position = 2 * depth - 2;
-        if layer % scanner_position == 0 {
-            severity += layer * depth;
+        for (layer, depth) in firewalls.iter() {
+            let scanner_position = 2 * depth - 2;
+            if layer % scanner_position == 0 {
+                severity += layer * depth;
+            }
         }
+        severity
     }
-    severity
-}
 
-pub fn answer2(input: &str) -> u32 {
-    let firewalls = parse_input(&input);
-    let firewalls: HashMap<u32, u32> = firewalls.iter().map(|f| (f.layer, f.depth)).collect();
-
-    // no need for the expansive state structure and no need to compute the back-and-forth
-    // we can compute the effective position of the scanner if it was always going straight
-    // and compare it to the effective position of the packet if it was always going straight
-    (0..)
-        .find(|delay| {
-            for (layer, depth) in firewalls.iter() {
-                let position = layer + delay;
-                let scanner_position = 2 * depth - 2;
-                if position % scanner_position == 0 {
-                    return false;
+    fn answer2(firewalls: &Vec<Firewall>) -> u32 {
+        let firewalls: HashMap<u32, u32> = firewalls.iter().map(|f| (f.layer, f.depth)).collect();
+
+        // no need for the expansive state structure and no need to compute the back-and-forth
+        // we can compute the effective position of the scanner if it was always going straight
+        // and compare it to the effective position of the packet if it was always going straight
+        (0..)
+            .find(|delay| {
+                for (layer, depth) in firewalls.iter() {
+                    let position = layer + delay;
+                    let scanner_position = 2 * depth - 2;
+                    if position % scanner_position == 0 {
+                        return false;
+                    }
                 }
-            }
-            true
-        })
-        .unwrap()
+                true
+            })
+            .unwrap()
+    }
+}
+
+#[test]
+fn test_parse_input() {
+    assert_eq!(
+        Day13::parse_input("0: 3\n1: 1").unwrap_err().to_string(),
+        "day 13, line 2, column 4: expected a depth of at least 2, found `1`"
+    );
+    assert!(Day13::parse_input("0: 0").is_err());
 }
 
 #[test]
@@ -86,7 +102,7 @@ fn test_answer1() {
 6: 4"#,
     );
 
-    assert_eq!(answer1(&input), 0 * 3 + 6 * 4);
+    assert_eq!(Day13::run1(&input).unwrap(), 6 * 4);
 }
 
 #[test]
@@ -99,5 +115,5 @@ fn test_answer2() {
 6: 4"#,
     );
 
-    assert_eq!(answer2(&input), 10);
+    assert_eq!(Day13::run2(&input).unwrap(), 10);
 }
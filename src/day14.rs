@@ -1,71 +1,44 @@
+use crate::common;
 use crate::day10;
+use crate::error::LineError;
+use crate::grid::Grid;
+use crate::{ParseError, Solver};
 
-pub fn title() -> &'static str {
-    "Day 14: Disk Defragmentation"
-}
+pub struct Day14;
 
-fn from_char_count_ones(c: char) -> u32 {
-    match c {
-        '0' => 0, //0000
-        '1' => 1, //0001
-        '2' => 1, //0010
-        '3' => 2, //0011
-        '4' => 1, //0100
-        '5' => 2, //0101
-        '6' => 2, //0110
-        '7' => 3, //0111
-        '8' => 1, //1000
-        '9' => 2, //1001
-        'a' => 2, //1010
-        'b' => 3, //1011
-        'c' => 2, //1100
-        'd' => 3, //1101
-        'e' => 3, //1110
-        'f' => 4, //1111
-        _ => unreachable!(),
-    }
+// the inputs of the knot hashes of the rows
+fn rows(key: &str) -> Vec<String> {
+    (0..128).map(|i| format!("{}-{}", key, i)).collect()
 }
 
-fn from_char_to_bytes(c: char) -> Vec<bool> {
-    match c {
-        '0' => vec![false, false, false, false], //0000
-        '1' => vec![false, false, false, true],  //0001
-        '2' => vec![false, false, true, false],  //0010
-        '3' => vec![false, false, true, true],   //0011
-        '4' => vec![false, true, false, false],  //0100
-        '5' => vec![false, true, false, true],   //0101
-        '6' => vec![false, true, true, false],   //0110
-        '7' => vec![false, true, true, true],    //0111
-        '8' => vec![true, false, false, false],  //1000
-        '9' => vec![true, false, false, true],   //1001
-        'a' => vec![true, false, true, false],   //1010
-        'b' => vec![true, false, true, true],    //1011
-        'c' => vec![true, true, false, false],   //1100
-        'd' => vec![true, true, false, true],    //1101
-        'e' => vec![true, true, true, false],    //1110
-        'f' => vec![true, true, true, true],     //1111
-        _ => unreachable!(),
-    }
+/// The used (`#`) and free (`.`) squares of the disk, one row per knot hash.
+pub fn disk(key: &str) -> Grid<bool> {
+    Grid::from_rows(
+        day10::digests(&rows(key))
+            .iter()
+            .map(|digest| digest.bits().collect())
+            .collect(),
+    )
 }
 
-fn neighbours(grid: &[Vec<bool>]) -> Vec<Vec<usize>> {
+fn neighbours(grid: &Grid<bool>) -> Vec<Vec<usize>> {
+    let used = |x: usize, y: usize| grid.get(x, y) == Some(true);
     let mut neighbours = vec![vec![]];
-    for x in 0..128 {
-        for y in 0..128 {
-            if grid[x][y] {
-                let mut ns = vec![];
-                ns.push(x * 128 + y);
-                if x > 0 && grid[x - 1][y] {
-                    ns.push((x - 1) * 128 + y);
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            if used(x, y) {
+                let mut ns = vec![y * 128 + x];
+                if y > 0 && used(x, y - 1) {
+                    ns.push((y - 1) * 128 + x);
                 }
-                if x < 126 && grid[x + 1][y] {
-                    ns.push((x + 1) * 128 + y);
+                if used(x, y + 1) {
+                    ns.push((y + 1) * 128 + x);
                 }
-                if y > 0 && grid[x][y - 1] {
-                    ns.push(x * 128 + y - 1);
+                if x > 0 && used(x - 1, y) {
+                    ns.push(y * 128 + x - 1);
                 }
-                if y < 126 && grid[x][y + 1] {
-                    ns.push(x * 128 + y + 1);
+                if used(x + 1, y) {
+                    ns.push(y * 128 + x + 1);
                 }
                 neighbours.push(ns);
             }
@@ -75,43 +48,58 @@ fn neighbours(grid: &[Vec<bool>]) -> Vec<Vec<usize>> {
     neighbours
 }
 
-pub fn answer1(input: &str) -> u32 {
-    (0..128)
-        .map(|i| {
-            let s = format!("{}-{}", input, i);
-            day10::hash(&s)
-                .chars()
-                .fold(0, |count, c| count + from_char_count_ones(c))
+impl Solver for Day14 {
+    const DAY: u32 = 14;
+    const TITLE: &'static str = "Day 14: Disk Defragmentation";
+
+    type Input = String;
+    type Output1 = u32;
+    type Output2 = usize;
+
+    fn parse_input(input: &str) -> Result<String, ParseError> {
+        common::parse_line(Self::DAY, input, |line| match line {
+            "" => Err(LineError::new(0, "a key")),
+            key => Ok(key.to_string()),
         })
-        .sum()
-}
+    }
 
-pub fn answer2(input: &str) -> usize {
-    let mut grid: Vec<Vec<bool>> = vec![vec![]; 128];
-    (0..128).for_each(|i| {
-        let s = format!("{}-{}", input, i);
-        grid[i].extend(
-            day10::hash(&s)
-                .chars()
-                .flat_map(from_char_to_bytes)
-                .collect::<Vec<bool>>(),
-        );
-    });
-    let neighbours = neighbours(&grid);
+    fn answer1(key: &String) -> u32 {
+        day10::digests(&rows(key))
+            .iter()
+            .map(|digest| digest.count_ones())
+            .sum()
+    }
+
+    fn answer2(key: &String) -> usize {
+        let neighbours = neighbours(&disk(key));
 
-    // https://docs.rs/pathfinding/1.1.10/pathfinding/undirected/connected_components/fn.components.html
-    let groups = pathfinding::undirected::connected_components::components(&neighbours);
-    groups.len()
+        // https://docs.rs/pathfinding/1.1.10/pathfinding/undirected/connected_components/fn.components.html
+        let groups = pathfinding::undirected::connected_components::components(&neighbours);
+        groups.len()
+    }
 }
 
 #[test]
 fn test_answer1() {
     let input = String::from("flqrgnkx");
-    assert_eq!(answer1(&input), 8108);
+    assert_eq!(Day14::run1(&input).unwrap(), 8108);
+}
+
+#[test]
+fn test_disk() {
+    let disk = disk("flqrgnkx").to_string();
+    let corner: Vec<&str> = disk.lines().take(8).map(|l| &l[..8]).collect();
+    assert_eq!(
+        corner,
+        vec![
+            "##.#.#..", ".#.#.#.#", "....#.#.", "#.#.##.#", ".##.#...", "##..#..#", ".#...#..",
+            "##.#.##.",
+        ]
+    );
 }
 
 #[test]
 fn test_answer2() {
     let input = String::from("flqrgnkx");
-    assert_eq!(answer2(&input), 1242);
+    assert_eq!(Day14::run2(&input).unwrap(), 1242);
 }
@@ -1,5 +1,14 @@
-pub fn title() -> &'static str {
-    "Day 15: Dueling Generators"
+use crate::common;
+use crate::error::LineError;
+use crate::{ParseError, Solver};
+
+pub struct Day15;
+
+// starting values of the generators A and B
+#[derive(Debug, PartialEq)]
+pub struct Seeds {
+    a: u64,
+    b: u64,
 }
 
 struct Generator {
@@ -25,46 +34,94 @@ impl Generator {
 
     fn next_2(&mut self) {
         self.next();
-        while self.value % self.acceptable != 0 {
+        while !self.value.is_multiple_of(self.acceptable) {
             self.next();
         }
     }
 }
 
-pub fn answer1(a: u64, b: u64) -> u32 {
-    let mut gen_a = Generator::new(16807, 2_147_483_647, 4, a);
-    let mut gen_b = Generator::new(48271, 2_147_483_647, 8, b);
+// a line like "Generator A starts with 65", the prefix being quoted in backticks
+fn parse_seed(line: &str, prefix: &'static str) -> Result<u64, LineError> {
+    let mut words = common::words(line);
+    for expected in prefix.trim_matches('`').split(' ') {
+        match words.next() {
+            Some((_, word)) if word == expected => {}
+            Some((offset, _)) => return Err(LineError::new(offset, prefix)),
+            None => return Err(LineError::new(line.len(), prefix)),
+        }
+    }
+    let seed = match words.next() {
+        Some((offset, word)) => word
+            .parse()
+            .map_err(|_| LineError::new(offset, "a starting value"))?,
+        None => return Err(LineError::new(line.len(), "a starting value")),
+    };
+    match words.next() {
+        Some((offset, _)) => Err(LineError::new(offset, "end of line")),
+        None => Ok(seed),
+    }
+}
+
+impl Solver for Day15 {
+    const DAY: u32 = 15;
+    const TITLE: &'static str = "Day 15: Dueling Generators";
 
-    let mask = 0b0000_0000_0000_0000_1111_1111_1111_1111u64;
+    type Input = Seeds;
+    type Output1 = u32;
+    type Output2 = u32;
 
-    let mut count = 0;
-    (0..40_000_000).for_each(|_| {
-        gen_a.next();
-        gen_b.next();
-        if (gen_a.value & mask) == (gen_b.value & mask) {
-            count += 1;
+    fn parse_input(input: &str) -> Result<Seeds, ParseError> {
+        let lines: Vec<&str> = input.trim_end().split('\n').collect();
+        if lines.len() > 2 {
+            let error = LineError::new(0, "two lines");
+            return Err(ParseError::new(Self::DAY, 3, lines[2], error));
         }
-    });
 
-    count
-}
+        let seed = |n: usize, prefix: &'static str| {
+            let line = lines.get(n).copied().unwrap_or("");
+            parse_seed(line, prefix).map_err(|e| ParseError::new(Self::DAY, n + 1, line, e))
+        };
+        Ok(Seeds {
+            a: seed(0, "`Generator A starts with`")?,
+            b: seed(1, "`Generator B starts with`")?,
+        })
+    }
 
-pub fn answer2(a: u64, b: u64) -> u32 {
-    let mut gen_a = Generator::new(16807, 2_147_483_647, 4, a);
-    let mut gen_b = Generator::new(48271, 2_147_483_647, 8, b);
+    fn answer1(seeds: &Seeds) -> u32 {
+        let mut gen_a = Generator::new(16807, 2_147_483_647, 4, seeds.a);
+        let mut gen_b = Generator::new(48271, 2_147_483_647, 8, seeds.b);
 
-    let mask = 0b0000_0000_0000_0000_1111_1111_1111_1111u64;
+        let mask = 0b0000_0000_0000_0000_1111_1111_1111_1111u64;
 
-    let mut count = 0;
-    (0..5_000_000).for_each(|_| {
-        gen_a.next_2();
-        gen_b.next_2();
-        if (gen_a.value & mask) == (gen_b.value & mask) {
-            count += 1;
-        }
-    });
+        let mut count = 0;
+        (0..40_000_000).for_each(|_| {
+            gen_a.next();
+            gen_b.next();
+            if (gen_a.value & mask) == (gen_b.value & mask) {
+                count += 1;
+            }
+        });
 
-    count
+        count
+    }
+
+    fn answer2(seeds: &Seeds) -> u32 {
+        let mut gen_a = Generator::new(16807, 2_147_483_647, 4, seeds.a);
+        let mut gen_b = Generator::new(48271, 2_147_483_647, 8, seeds.b);
+
+        let mask = 0b0000_0000_0000_0000_1111_1111_1111_1111u64;
+
+        let mut count = 0;
+        (0..5_000_000).for_each(|_| {
+            gen_a.next_2();
+            gen_b.next_2();
+            if (gen_a.value & mask) == (gen_b.value & mask) {
+                count += 1;
+            }
+        });
+
+        count
+    }
 }
 
 #[test]
@@ -97,12 +154,38 @@ fn test_generator_a_next_2() {
     assert_eq!(gen_a.value, 740335192);
 }
 
+#[test]
+fn test_parse_input() {
+    assert_eq!(
+        Day15::parse_input("Generator A starts with 65\nGenerator B starts with 8921\n").unwrap(),
+        Seeds { a: 65, b: 8921 }
+    );
+    assert_eq!(
+        Day15::parse_input("Generator A starts with 65\nGenerator A starts with 8921")
+            .unwrap_err()
+            .to_string(),
+        "day 15, line 2, column 11: expected `Generator B starts with`, found `A`"
+    );
+    assert_eq!(
+        Day15::parse_input("Generator A starts with 65")
+            .unwrap_err()
+            .to_string(),
+        "day 15, line 2, column 1: expected `Generator B starts with`, found end of line"
+    );
+    assert_eq!(
+        Day15::parse_input("Generator A starts with x\n")
+            .unwrap_err()
+            .to_string(),
+        "day 15, line 1, column 25: expected a starting value, found `x`"
+    );
+}
+
 #[test]
 fn test_answer1() {
-    assert_eq!(answer1(65, 8921), 588);
+    assert_eq!(Day15::answer1(&Seeds { a: 65, b: 8921 }), 588);
 }
 
 #[test]
 fn test_answer2() {
-    assert_eq!(answer2(65, 8921), 309);
+    assert_eq!(Day15::answer2(&Seeds { a: 65, b: 8921 }), 309);
 }
@@ -1,9 +1,9 @@
-use std::mem;
+use crate::common;
+use crate::error::LineError;
+use crate::{ParseError, Solver};
 use std::str::FromStr;
 
-pub fn title() -> &'static str {
-    "Day 16: Permutation Promenade"
-}
+pub struct Day16;
 
 #[derive(Debug, PartialEq)]
 enum Dance {
@@ -16,37 +16,52 @@ impl FromStr for Dance {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Dance, ()> {
-        let bytes = s.as_bytes();
-        match bytes[0] {
-            b's' => Ok(Dance::parse_spin(s)),
-            b'x' => Ok(Dance::parse_exchange(s)),
-            b'p' => Ok(Dance::parse_partner(s)),
+        match s.as_bytes().first() {
+            Some(b's') => Dance::parse_spin(&s[1..]),
+            Some(b'x') => Dance::parse_exchange(&s[1..]),
+            Some(b'p') => Dance::parse_partner(&s[1..]),
             _ => Err(()),
         }
     }
 }
 
 impl Dance {
-    fn parse_spin(s: &str) -> Dance {
-        let parse = s.get(1..).unwrap();
-        Dance::Spin(usize::from_str_radix(parse, 10).unwrap())
+    fn parse_spin(s: &str) -> Result<Dance, ()> {
+        Ok(Dance::Spin(s.parse().map_err(|_| ())?))
     }
 
-    fn parse_exchange(s: &str) -> Dance {
-        let mut parse = s.get(1..).unwrap().split('/');
-        let idx1 = parse.next().unwrap();
-        let idx2 = parse.next().unwrap();
-        Dance::Exchange(
-            usize::from_str_radix(idx1, 10).unwrap(),
-            usize::from_str_radix(idx2, 10).unwrap(),
-        )
+    fn parse_exchange(s: &str) -> Result<Dance, ()> {
+        let (idx1, idx2) = Dance::parse_pair(s)?;
+        Ok(Dance::Exchange(
+            idx1.parse().map_err(|_| ())?,
+            idx2.parse().map_err(|_| ())?,
+        ))
+    }
+
+    fn parse_partner(s: &str) -> Result<Dance, ()> {
+        let (p1, p2) = Dance::parse_pair(s)?;
+        if p1.len() != 1 || p2.len() != 1 {
+            return Err(());
+        }
+        Ok(Dance::Partner(common::to_char(p1), common::to_char(p2)))
+    }
+
+    // whether the move only involves the first `size` programs
+    fn fits(&self, size: usize) -> bool {
+        let program = |p: char| p >= 'a' && ((p as u8 - b'a') as usize) < size;
+        match *self {
+            Dance::Spin(idx) => idx <= size,
+            Dance::Exchange(idx1, idx2) => idx1 < size && idx2 < size,
+            Dance::Partner(p1, p2) => program(p1) && program(p2),
+        }
     }
 
-    fn parse_partner(s: &str) -> Dance {
-        let mut parse = s.get(1..).unwrap().split('/');
-        let p1 = parse.next().unwrap();
-        let p2 = parse.next().unwrap();
-        Dance::Partner(p1.as_bytes()[0] as char, p2.as_bytes()[0] as char)
+    fn parse_pair(s: &str) -> Result<(&str, &str), ()> {
+        let mut parse = s.split('/');
+        match (parse.next(), parse.next(), parse.next()) {
+            (Some(a), Some(b), None) => Ok((a, b)),
+            _ => Err(()),
+        }
     }
 
     fn apply(&self, programs: &mut Vec<char>) {
@@ -55,7 +70,7 @@ impl Dance {
                 let (start, end) = programs.split_at(programs.len() - *idx);
                 let mut new = end.to_vec();
                 new.extend_from_slice(start);
-                mem::replace(programs, new);
+                *programs = new;
             }
             Dance::Exchange(idx1, idx2) => {
                 programs.swap(*idx1, *idx2);
@@ -69,40 +84,74 @@ impl Dance {
     }
 }
 
-pub fn answer1(input: &str, size: usize) -> String {
-    let dances = parse_input(&input);
-    let mut programs = vec_chars(size);
-    dances.iter().for_each(|d| d.apply(&mut programs));
-    programs.iter().collect()
+// the dance moves, and the number of programs dancing
+#[derive(Debug, PartialEq)]
+pub struct Routine {
+    size: usize,
+    dances: Vec<Dance>,
 }
 
-pub fn answer2(input: &str, size: usize) -> String {
-    let dances = parse_input(&input);
-    let mut programs = vec_chars(size);
+impl Solver for Day16 {
+    const DAY: u32 = 16;
+    const TITLE: &'static str = "Day 16: Permutation Promenade";
+
+    type Input = Routine;
+    type Output1 = String;
+    type Output2 = String;
+
+    // the examples have fewer programs dancing, e.g. `size: 5`
+    fn parse_input(input: &str) -> Result<Routine, ParseError> {
+        let size = |v: &str| v.parse().ok().filter(|s| (1..=26).contains(s));
+        common::parse_with_parameter(
+            Self::DAY,
+            input,
+            "size",
+            size,
+            "a number of programs from 1 to 26",
+            |size, input| {
+                let size = size.unwrap_or(16);
+                let dances = common::parse_line(Self::DAY, input, |line| {
+                    line.split(',')
+                        .map(|m| {
+                            let offset = common::offset(line, m);
+                            match Dance::from_str(m) {
+                                Ok(dance) if dance.fits(size) => Ok(dance),
+                                Ok(_) => {
+                                    Err(LineError::new(offset, "a move of the programs dancing"))
+                                }
+                                Err(_) => Err(LineError::new(offset, "a move (sX, xA/B or pA/B)")),
+                            }
+                        })
+                        .collect()
+                })?;
+
+                Ok(Routine { size, dances })
+            },
+        )
+    }
+
+    fn answer1(routine: &Routine) -> String {
+        let mut programs = vec_chars(routine.size);
+        routine.dances.iter().for_each(|d| d.apply(&mut programs));
+        programs.iter().collect()
+    }
 
-    let mut seen: Vec<Vec<char>> = vec![];
+    fn answer2(routine: &Routine) -> String {
+        let mut programs = vec_chars(routine.size);
 
-    let mut count = 0;
-    let remaining_dances = loop {
-        dances.iter().for_each(|d| d.apply(&mut programs));
-        let already_seen = seen.iter().position(|p| *p == programs);
+        let mut seen: Vec<Vec<char>> = vec![];
 
-        if already_seen.is_none() {
-            count += 1;
+        // the dance is a permutation, so the first state to come back is the one after
+        // the first dance, and the states after n dances cycle from there
+        loop {
+            routine.dances.iter().for_each(|d| d.apply(&mut programs));
+            if seen.first() == Some(&programs) {
+                break;
+            }
             seen.push(programs.clone());
-        } else {
-            break 1_000_000_000 % count;
         }
-    };
-    seen[remaining_dances - 1].iter().collect()
-}
-
-fn parse_input(input: &str) -> Vec<Dance> {
-    let mut lines = input.split('\n').filter(|l| *l != "");
-    let line = lines.next().unwrap();
-    let moves = line.split(',');
-
-    moves.map(|m| Dance::from_str(m).unwrap()).collect()
+        seen[(1_000_000_000 - 1) % seen.len()].iter().collect()
+    }
 }
 
 fn vec_chars(size: usize) -> Vec<char> {
@@ -115,15 +164,38 @@ fn vec_chars(size: usize) -> Vec<char> {
 #[test]
 fn test_parse_input() {
     assert_eq!(
-        parse_input("s1,x3/4,pe/b"),
-        vec![
-            Dance::Spin(1),
-            Dance::Exchange(3, 4),
-            Dance::Partner('e', 'b')
-        ]
+        Day16::parse_input("s1,x3/4,pe/b").unwrap(),
+        Routine {
+            size: 16,
+            dances: vec![
+                Dance::Spin(1),
+                Dance::Exchange(3, 4),
+                Dance::Partner('e', 'b')
+            ]
+        }
     );
 }
 
+#[test]
+fn test_parse_input_error() {
+    assert_eq!(
+        Day16::parse_input("s1,x3/4/5,pe/b")
+            .unwrap_err()
+            .to_string(),
+        "day 16, line 1, column 4: expected a move (sX, xA/B or pA/B), found `x3/4/5`"
+    );
+    assert!(Day16::parse_input("s1,sx").is_err());
+    assert!(Day16::parse_input("s1,pe/bc").is_err());
+    assert!(Day16::parse_input("s1,").is_err());
+    assert_eq!(
+        Day16::parse_input("s1,x16/1").unwrap_err().to_string(),
+        "day 16, line 1, column 4: expected a move of the programs dancing, found `x16/1`"
+    );
+    assert!(Day16::parse_input("s17").is_err());
+    assert!(Day16::parse_input("pz/a").is_err());
+    assert!(Day16::parse_input("s16,x0/15,pa/p").is_ok());
+}
+
 #[test]
 fn test_vec_chars() {
     assert_eq!(vec_chars(5), vec!['a', 'b', 'c', 'd', 'e']);
@@ -131,11 +203,18 @@ fn test_vec_chars() {
 
 #[test]
 fn test_answer1() {
-    assert_eq!(answer1("s1,x3/4,pe/b", 5), "baedc");
+    assert_eq!(Day16::run1("size: 5\ns1,x3/4,pe/b").unwrap(), "baedc");
+    assert!(Day16::parse_input("size: 5\ns1,x3/4,pf/b").is_err());
+}
+
+#[test]
+fn test_answer2_example() {
+    // the example comes back to its start after a number of dances dividing a billion
+    assert_eq!(Day16::run2("size: 5\ns1,x3/4,pe/b").unwrap(), "abcde");
 }
 
 #[test]
 fn test_answer2() {
     let input16 = std::fs::read_to_string("input/input16.txt").unwrap();
-    assert_eq!(answer2(&input16, 16), "ejkflpgnamhdcboi");
+    assert_eq!(Day16::run2(&input16).unwrap(), "ejkflpgnamhdcboi");
 }
@@ -1,6 +1,8 @@
-pub fn title() -> &'static str {
-    "Day 17: Spinlock"
-}
+use crate::common;
+use crate::error::LineError;
+use crate::{ParseError, Solver};
+
+pub struct Day17;
 
 fn iterate(iterations: usize, input: usize) -> Vec<usize> {
     let mut values: Vec<usize> = Vec::with_capacity(iterations);
@@ -23,27 +25,42 @@ fn iterate(iterations: usize, input: usize) -> Vec<usize> {
     values
 }
 
-pub fn answer1(input: usize) -> usize {
-    let values = iterate(2017, input);
-    values[2017]
-}
+impl Solver for Day17 {
+    const DAY: u32 = 17;
+    const TITLE: &'static str = "Day 17: Spinlock";
+
+    type Input = usize;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn parse_input(input: &str) -> Result<usize, ParseError> {
+        common::parse_line(Self::DAY, input, |line| {
+            line.parse()
+                .map_err(|_| LineError::new(0, "a number of steps"))
+        })
+    }
+
+    fn answer1(steps: &usize) -> usize {
+        let values = iterate(2017, *steps);
+        values[2017]
+    }
 
-pub fn answer2(input: usize) -> usize {
-    let mut buflen = 1;
-    let mut pos = 0;
-    let mut result = 0;
+    fn answer2(steps: &usize) -> usize {
+        let mut pos = 0;
+        let mut result = 0;
 
-    for i in 1..50_000_000 {
-        pos = (pos + input) % buflen + 1;
-        buflen += 1;
-        if pos == 1 {
-            result = i;
+        // the buffer holds i values when inserting the value i
+        for i in 1..50_000_000 {
+            pos = (pos + steps) % i + 1;
+            if pos == 1 {
+                result = i;
+            }
         }
+        result
     }
-    result
 }
 
 #[test]
 fn test_answer1() {
-    assert_eq!(answer1(3), 638);
+    assert_eq!(Day17::answer1(&3), 638);
 }
@@ -1,13 +1,16 @@
 use crate::common;
-use std::collections::{HashMap, VecDeque};
+use crate::duet::{Mode, Network, Topology};
+use crate::error::LineError;
+use crate::vm::{Bytecode, Io, Op, Operand, Receive, Reg, Vm};
+use crate::{ParseError, Solver};
+use std::collections::VecDeque;
+use std::fmt;
 use std::str::FromStr;
 
-pub fn title() -> &'static str {
-    "Day 18: Duet"
-}
+pub struct Day18;
 
 #[derive(Debug, PartialEq)]
-enum Register {
+pub enum Register {
     Var(char),
     Val(i64),
 }
@@ -16,222 +19,200 @@ impl FromStr for Register {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Register, ()> {
-        let val = i64::from_str_radix(s, 10);
+        let val = s.parse::<i64>();
         match val {
             Ok(val) => Ok(Register::Val(val)),
-            _ => Ok(Register::Var(common::to_char(s))),
+            _ => parse_name(s).map(Register::Var),
         }
     }
 }
 
+// registers are named with a single lowercase letter
+fn parse_name(s: &str) -> Result<char, ()> {
+    match s.as_bytes() {
+        [c @ b'a'..=b'z'] => Ok(*c as char),
+        _ => Err(()),
+    }
+}
+
 #[derive(Debug, PartialEq)]
-enum Instruction {
+pub enum Instruction {
     Send(Register),
     Set(char, Register),
     Add(char, Register),
+    Sub(char, Register),
     Mul(char, Register),
     Mod(char, Register),
     Recover(char),
     Jump(Register, Register),
+    JumpNotZero(Register, Register),
 }
 
 impl FromStr for Instruction {
-    type Err = ();
+    type Err = LineError;
+
+    fn from_str(s: &str) -> Result<Instruction, LineError> {
+        let mut words = common::words(s);
+        let name = |words: &mut dyn Iterator<Item = (usize, &str)>| {
+            let (offset, word) = words
+                .next()
+                .ok_or_else(|| LineError::new(s.len(), "a register"))?;
+            parse_name(word).map_err(|_| LineError::new(offset, "a register"))
+        };
+        let register = |words: &mut dyn Iterator<Item = (usize, &str)>| {
+            let (offset, word) = words
+                .next()
+                .ok_or_else(|| LineError::new(s.len(), "a register or a value"))?;
+            Register::from_str(word).map_err(|_| LineError::new(offset, "a register or a value"))
+        };
+
+        let instruction = match words.next() {
+            Some((_, "snd")) => Instruction::Send(register(&mut words)?),
+            Some((_, "set")) => Instruction::Set(name(&mut words)?, register(&mut words)?),
+            Some((_, "add")) => Instruction::Add(name(&mut words)?, register(&mut words)?),
+            Some((_, "sub")) => Instruction::Sub(name(&mut words)?, register(&mut words)?),
+            Some((_, "mul")) => Instruction::Mul(name(&mut words)?, register(&mut words)?),
+            Some((_, "mod")) => Instruction::Mod(name(&mut words)?, register(&mut words)?),
+            Some((_, "rcv")) => Instruction::Recover(name(&mut words)?),
+            Some((_, "jgz")) => Instruction::Jump(register(&mut words)?, register(&mut words)?),
+            Some((_, "jnz")) => {
+                Instruction::JumpNotZero(register(&mut words)?, register(&mut words)?)
+            }
+            Some((offset, _)) => return Err(LineError::new(offset, "an instruction")),
+            None => return Err(LineError::new(0, "an instruction")),
+        };
 
-    fn from_str(s: &str) -> Result<Instruction, ()> {
-        let mut words = s.split(' ');
         match words.next() {
-            Some("snd") => Ok(Instruction::Send(
-                Register::from_str(words.next().unwrap()).unwrap(),
-            )),
-            Some("set") => Ok(Instruction::Set(
-                common::to_char(words.next().unwrap()),
-                Register::from_str(words.next().unwrap()).unwrap(),
-            )),
-            Some("add") => Ok(Instruction::Add(
-                common::to_char(words.next().unwrap()),
-                Register::from_str(words.next().unwrap()).unwrap(),
-            )),
-            Some("mul") => Ok(Instruction::Mul(
-                common::to_char(words.next().unwrap()),
-                Register::from_str(words.next().unwrap()).unwrap(),
-            )),
-            Some("mod") => Ok(Instruction::Mod(
-                common::to_char(words.next().unwrap()),
-                Register::from_str(words.next().unwrap()).unwrap(),
-            )),
-            Some("rcv") => Ok(Instruction::Recover(common::to_char(words.next().unwrap()))),
-            Some("jgz") => Ok(Instruction::Jump(
-                Register::from_str(words.next().unwrap()).unwrap(),
-                Register::from_str(words.next().unwrap()).unwrap(),
-            )),
-            _ => Err(()),
+            Some((offset, _)) => Err(LineError::new(offset, "end of line")),
+            None => Ok(instruction),
         }
     }
 }
 
-#[derive(Debug)]
-struct Program<'a> {
-    regs: HashMap<char, i64>,
-    pc: i64,
-    instructions: &'a [Instruction],
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Register::Var(name) => write!(f, "{}", name),
+            Register::Val(v) => write!(f, "{}", v),
+        }
+    }
 }
 
-impl<'a> Program<'a> {
-    fn new(instructions: &[Instruction], id: i64) -> Program {
-        Program {
-            regs: (97u8..123u8)
-                .map(|x| (x as char, if x == 112 { id } else { 0 }))
-                .collect(),
-            pc: 0,
-            instructions,
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Send(rx) => write!(f, "snd {}", rx),
+            Instruction::Set(rx, ry) => write!(f, "set {} {}", rx, ry),
+            Instruction::Add(rx, ry) => write!(f, "add {} {}", rx, ry),
+            Instruction::Sub(rx, ry) => write!(f, "sub {} {}", rx, ry),
+            Instruction::Mul(rx, ry) => write!(f, "mul {} {}", rx, ry),
+            Instruction::Mod(rx, ry) => write!(f, "mod {} {}", rx, ry),
+            Instruction::Recover(rx) => write!(f, "rcv {}", rx),
+            Instruction::Jump(rx, ry) => write!(f, "jgz {} {}", rx, ry),
+            Instruction::JumpNotZero(rx, ry) => write!(f, "jnz {} {}", rx, ry),
         }
     }
+}
 
-    fn execute(&mut self, my_queue: &mut VecDeque<i64>, other_queue: &mut VecDeque<i64>) -> bool {
-        while let Some(instruction) = self.instructions.get(if self.pc < 0 {
-            usize::max_value()
-        } else {
-            self.pc as usize
-        }) {
-            self.pc += 1;
-            match instruction {
-                Instruction::Send(rx) => {
-                    other_queue.push_back(self.get_val(&rx));
-                }
-                Instruction::Set(rx, ry) => {
-                    self.regs.insert(*rx, self.get_val(&ry));
-                }
-                Instruction::Add(rx, ry) => {
-                    self.regs.insert(*rx, self.regs[rx] + self.get_val(&ry));
-                }
-                Instruction::Mul(rx, ry) => {
-                    self.regs.insert(*rx, self.regs[rx] * self.get_val(&ry));
-                }
-                Instruction::Mod(rx, ry) => {
-                    self.regs.insert(*rx, self.regs[rx] % self.get_val(&ry));
-                }
-                Instruction::Recover(rx) => match my_queue.pop_front() {
-                    Some(val) => {
-                        self.regs.insert(*rx, val);
-                    }
-                    None => {
-                        self.pc -= 1;
-                        return false;
-                    }
-                },
-                Instruction::Jump(rx, ry) => {
-                    if self.get_val(&rx) > 0 {
-                        self.pc += self.get_val(&ry) - 1;
-                    }
-                }
-            }
-        }
-        true
+/// Compile the instructions, registers `a` to `z` being the slots 0 to 25.
+pub fn compile(instructions: &[Instruction]) -> Bytecode {
+    let mut bytecode = Bytecode::default();
+    for c in b'a'..=b'z' {
+        bytecode.register(&(c as char).to_string());
     }
 
-    fn get_val(&self, r: &Register) -> i64 {
-        match r {
-            Register::Var(v) => self.regs[&v],
-            Register::Val(v) => *v,
-        }
+    let reg = |name: char| name as Reg - 'a' as Reg;
+    let operand = |r: &Register| match r {
+        Register::Var(name) => Operand::Reg(reg(*name)),
+        Register::Val(v) => Operand::Val(*v),
+    };
+    for instruction in instructions {
+        let op = match instruction {
+            Instruction::Send(rx) => Op::Snd(operand(rx)),
+            Instruction::Set(rx, ry) => Op::Set(reg(*rx), operand(ry)),
+            Instruction::Add(rx, ry) => Op::Add(reg(*rx), operand(ry)),
+            Instruction::Sub(rx, ry) => Op::Sub(reg(*rx), operand(ry)),
+            Instruction::Mul(rx, ry) => Op::Mul(reg(*rx), operand(ry)),
+            Instruction::Mod(rx, ry) => Op::Mod(reg(*rx), operand(ry)),
+            Instruction::Recover(rx) => Op::Rcv(reg(*rx)),
+            Instruction::Jump(rx, ry) => Op::Jgz(operand(rx), operand(ry)),
+            Instruction::JumpNotZero(rx, ry) => Op::Jnz(operand(rx), operand(ry)),
+        };
+        bytecode.push(op, None);
     }
+    bytecode
 }
 
-pub fn answer1(input: &str) -> i64 {
-    let instructions = parse_input(input);
-
-    let mut pc = 0;
-    let mut regs: HashMap<char, i64> = (97u8..123u8).map(|x| (x as char, 0)).collect();
-    let mut freq = 0;
-
-    while let Some(instruction) = instructions.get(if pc < 0 {
-        usize::max_value()
-    } else {
-        pc as usize
-    }) {
-        pc += 1;
-        match instruction {
-            Instruction::Send(rx) => {
-                freq = get_val(&regs, rx);
-            }
-            Instruction::Set(rx, ry) => {
-                regs.insert(*rx, get_val(&regs, ry));
-            }
-            Instruction::Add(rx, ry) => {
-                regs.insert(*rx, regs[rx] + get_val(&regs, ry));
-            }
-            Instruction::Mul(rx, ry) => {
-                regs.insert(*rx, regs[rx] * get_val(&regs, ry));
-            }
-            Instruction::Mod(rx, ry) => {
-                regs.insert(*rx, regs[rx] % get_val(&regs, ry));
-            }
-            Instruction::Recover(_rx) => {
-                return freq;
-            }
-            Instruction::Jump(rx, ry) => {
-                if get_val(&regs, rx) > 0 {
-                    pc += get_val(&regs, ry) - 1;
-                }
-            }
-        }
+/// Part 1: `snd` plays a sound, `rcv` recovers the last one played when its register is not 0.
+#[derive(Debug, Default)]
+pub struct Sound {
+    pub last: Option<i64>,
+}
+
+impl Io for Sound {
+    fn send(&mut self, value: i64) {
+        self.last = Some(value);
     }
 
-    freq
+    fn receive(&mut self, current: i64) -> Receive {
+        if current != 0 {
+            Receive::Halt
+        } else {
+            Receive::Skip
+        }
+    }
 }
 
-fn get_val(regs: &HashMap<char, i64>, r: &Register) -> i64 {
-    match r {
-        Register::Var(v) => regs[&v],
-        Register::Val(v) => *v,
-    }
+/// Part 2: `snd` and `rcv` go through a queue between two programs.
+#[derive(Debug)]
+pub struct Queues<'a> {
+    pub inbox: &'a mut VecDeque<i64>,
+    pub outbox: &'a mut VecDeque<i64>,
 }
 
-pub fn answer2(input: &str) -> usize {
-    let instructions = parse_input(input);
+impl<'a> Io for Queues<'a> {
+    fn send(&mut self, value: i64) {
+        self.outbox.push_back(value);
+    }
 
-    let mut program0 = Program::new(&instructions, 0);
-    let mut program1 = Program::new(&instructions, 1);
-    let mut queue0 = VecDeque::new();
-    let mut queue1 = VecDeque::new();
+    fn receive(&mut self, _: i64) -> Receive {
+        match self.inbox.pop_front() {
+            Some(value) => Receive::Value(value),
+            None => Receive::Wait,
+        }
+    }
+}
 
-    let mut counter = 0;
-    loop {
-        let done = program0.execute(&mut queue1, &mut queue0);
-        let len_before = queue1.len();
+impl Solver for Day18 {
+    const DAY: u32 = 18;
+    const TITLE: &'static str = "Day 18: Duet";
 
-        let done = done || program1.execute(&mut queue0, &mut queue1);
+    type Input = Vec<Instruction>;
+    type Output1 = i64;
+    type Output2 = usize;
 
-        counter += queue1.len() - len_before;
-        if done || queue1.is_empty() {
-            break;
-        }
+    fn parse_input(input: &str) -> Result<Vec<Instruction>, ParseError> {
+        common::parse_lines(Self::DAY, input, Instruction::from_str)
     }
 
-    counter
-}
-
-fn parse_input(input: &str) -> Vec<Instruction> {
-    let lines = input.split('\n');
-    let instructions: Vec<Instruction> = lines
-        .filter(|l| *l != "")
-        .map(|l| Instruction::from_str(l).unwrap())
-        .collect();
+    fn answer1(instructions: &Vec<Instruction>) -> i64 {
+        let bytecode = compile(instructions);
+        let mut sound = Sound::default();
+        Vm::new(&bytecode).run(&mut sound);
+        sound.last.unwrap_or(0)
+    }
 
-    instructions
+    fn answer2(instructions: &Vec<Instruction>) -> usize {
+        let bytecode = compile(instructions);
+        let network = Network::new(&bytecode, 2, Topology::Pairwise);
+        network.run(Mode::Cooperative).programs()[1].stats.sent
+    }
 }
 
 #[test]
 fn test_register_from_str() {
-    assert_eq!(
-        Register::from_str(&"a".to_string()).unwrap(),
-        Register::Var('a')
-    );
-    assert_eq!(
-        Register::from_str(&"-1".to_string()).unwrap(),
-        Register::Val(-1)
-    );
+    assert_eq!(Register::from_str("a").unwrap(), Register::Var('a'));
+    assert_eq!(Register::from_str("-1").unwrap(), Register::Val(-1));
 }
 
 #[test]
@@ -242,6 +223,39 @@ fn test_instruction_from_str() {
         Instruction::from_str(&input).unwrap(),
         Instruction::Jump(Register::Var('a'), Register::Val(-1))
     );
+    assert_eq!(Instruction::from_str(&input).unwrap().to_string(), input);
+}
+
+#[test]
+fn test_executed() {
+    let instructions = Day18::parse_input("set a 3\nsub a 1\njnz a -1\nmul b 2").unwrap();
+    let bytecode = compile(&instructions);
+    let mut program = Vm::new(&bytecode);
+    assert_eq!(program.run(&mut ()), crate::vm::State::Terminated);
+    assert_eq!(program.executed(), &[1, 3, 3, 1]);
+    assert_eq!((program.register("a"), program.pc()), (0, 4));
+}
+
+#[test]
+fn test_parse_input() {
+    assert_eq!(
+        Day18::parse_input("set a 1\nadd a\n")
+            .unwrap_err()
+            .to_string(),
+        "day 18, line 2, column 6: expected a register or a value, found end of line"
+    );
+    assert_eq!(
+        Day18::parse_input("set A 1").unwrap_err().to_string(),
+        "day 18, line 1, column 5: expected a register, found `A`"
+    );
+    assert_eq!(
+        Day18::parse_input("jmp a 1").unwrap_err().to_string(),
+        "day 18, line 1, column 1: expected an instruction, found `jmp`"
+    );
+    assert_eq!(
+        Day18::parse_input("rcv a b").unwrap_err().to_string(),
+        "day 18, line 1, column 7: expected end of line, found `b`"
+    );
 }
 
 #[test]
@@ -260,7 +274,7 @@ set a 1
 jgz a -2"#,
     );
 
-    assert_eq!(answer1(&input), 4);
+    assert_eq!(Day18::run1(&input).unwrap(), 4);
 }
 
 #[test]
@@ -276,5 +290,5 @@ rcv c
 rcv d"#,
     );
 
-    assert_eq!(answer2(&input), 3);
+    assert_eq!(Day18::run2(&input).unwrap(), 3);
 }
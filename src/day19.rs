@@ -1,57 +1,92 @@
-pub fn title() -> &'static str {
-    "Day 19: A Series of Tubes"
-}
+use crate::error::LineError;
+use crate::grid::{Dir, Grid};
+use crate::{ParseError, Solver};
+
+pub struct Day19;
+
+impl Solver for Day19 {
+    const DAY: u32 = 19;
+    const TITLE: &'static str = "Day 19: A Series of Tubes";
+
+    type Input = Grid<u8>;
+    type Output1 = String;
+    type Output2 = usize;
+
+    fn parse_input(input: &str) -> Result<Grid<u8>, ParseError> {
+        let mut grid: Vec<Vec<u8>> = input
+            .split('\n')
+            .filter(|l| !l.is_empty())
+            .map(|s| s.as_bytes().to_vec())
+            .collect();
+
+        // the path enters the grid from the top
+        let first_line = input.split('\n').position(|l| !l.is_empty()).unwrap_or(0);
+        if !grid.first().is_some_and(|l| l.contains(&b'|')) {
+            let line = input.split('\n').nth(first_line).unwrap_or("");
+            return Err(ParseError::new(
+                Self::DAY,
+                first_line + 1,
+                line,
+                LineError::new(0, "an entry point `|`"),
+            ));
+        }
 
-pub fn answer1(input: &str) -> String {
-    let grid = parse_input(input);
+        let x_max = grid.iter().map(|l| l.len()).max().unwrap();
 
-    // find starting x
-    let start_idx: usize = grid[0]
-        .iter()
-        .position(|&x| x == b'|')
-        .expect("Not starting x found");
+        // padd all the lines with ' ' to get a square grid
+        grid.iter_mut().for_each(|line| {
+            let padd_length = x_max - line.len();
+            (0..padd_length).for_each(|_| line.push(b' '));
+        });
 
-    let mut state = State::new(grid);
-    state.move_cell(start_idx, 0, Dir::Down);
-    while !state.is_end(state.x, state.y, state.dir) {
-        step(&mut state);
+        Ok(Grid::from_rows(grid))
     }
 
-    //https://stackoverflow.com/questions/41034635/idiomatic-transformations-for-string-str-vecu8-and-u8
-    String::from_utf8(state.current).unwrap()
-}
+    fn answer1(grid: &Grid<u8>) -> String {
+        // find starting x
+        let start_idx: usize = grid
+            .rows()
+            .next()
+            .unwrap()
+            .iter()
+            .position(|&x| x == b'|')
+            .expect("Not starting x found");
+
+        let mut state = State::new(grid.clone());
+        state.move_cell(start_idx, 0, Dir::Down);
+        while !state.is_end(state.x, state.y, state.dir) {
+            step(&mut state);
+        }
 
-pub fn answer2(input: &str) -> usize {
-    let grid = parse_input(input);
-
-    // find starting x
-    let start_idx: usize = grid[0]
-        .iter()
-        .position(|&x| x == b'|')
-        .expect("Not starting x found");
-
-    let mut state = State::new(grid);
-    let mut steps = 1; // because we don't step on the first cell
-    state.move_cell(start_idx, 0, Dir::Down);
-    while !state.is_end(state.x, state.y, state.dir) {
-        step(&mut state);
-        steps += 1;
+        //https://stackoverflow.com/questions/41034635/idiomatic-transformations-for-string-str-vecu8-and-u8
+        String::from_utf8(state.current).unwrap()
     }
 
-    steps
-}
+    fn answer2(grid: &Grid<u8>) -> usize {
+        // find starting x
+        let start_idx: usize = grid
+            .rows()
+            .next()
+            .unwrap()
+            .iter()
+            .position(|&x| x == b'|')
+            .expect("Not starting x found");
+
+        let mut state = State::new(grid.clone());
+        let mut steps = 1; // because we don't step on the first cell
+        state.move_cell(start_idx, 0, Dir::Down);
+        while !state.is_end(state.x, state.y, state.dir) {
+            step(&mut state);
+            steps += 1;
+        }
 
-#[derive(Debug, Clone, Copy)]
-enum Dir {
-    Left,
-    Right,
-    Down,
-    Up,
+        steps
+    }
 }
 
 #[derive(Debug)]
 struct State {
-    grid: Vec<Vec<u8>>,
+    grid: Grid<u8>,
     x: usize,
     y: usize,
     dir: Dir,
@@ -59,7 +94,7 @@ struct State {
 }
 
 impl State {
-    fn new(grid: Vec<Vec<u8>>) -> Self {
+    fn new(grid: Grid<u8>) -> Self {
         State {
             grid,
             x: 0,
@@ -70,7 +105,7 @@ impl State {
     }
 
     fn move_cell(&mut self, x: usize, y: usize, direction: Dir) {
-        let cur = self.grid[y][x];
+        let cur = self.grid[(x, y)];
         if cur != b' ' && cur != b'+' && cur != b'-' && cur != b'|' {
             self.current.push(cur);
         }
@@ -81,11 +116,11 @@ impl State {
     }
 
     fn is_valid(&self, x: usize, y: usize) -> bool {
-        x > 0 && x < self.grid[0].len() && y > 0 && y < self.grid.len()
+        x > 0 && x < self.grid.width() && y > 0 && y < self.grid.height()
     }
 
     fn is_blank(&self, x: usize, y: usize) -> bool {
-        self.grid[y][x] == b' '
+        self.grid[(x, y)] == b' '
     }
 
     fn is_valid_blank(&self, x: usize, y: usize) -> bool {
@@ -97,7 +132,7 @@ impl State {
     }
 
     fn is_end(&self, x: usize, y: usize, direction: Dir) -> bool {
-        if self.grid[y][x] == b'+' {
+        if self.grid[(x, y)] == b'+' {
             match direction {
                 Dir::Up => self.is_valid_blank(x - 1, y) && self.is_valid_blank(x + 1, y),
                 Dir::Down => self.is_valid_blank(x - 1, y) && self.is_valid_blank(x + 1, y),
@@ -116,7 +151,7 @@ impl State {
 }
 
 fn step(s: &mut State) {
-    match (s.grid[s.y][s.x], s.dir) {
+    match (s.grid[(s.x, s.y)], s.dir) {
         (b'+', Dir::Up) => {
             if s.is_valid_not_blank(s.x - 1, s.y) {
                 s.move_cell(s.x - 1, s.y, Dir::Left)
@@ -160,22 +195,11 @@ fn step(s: &mut State) {
     };
 }
 
-pub fn parse_input(input: &str) -> Vec<Vec<u8>> {
-    let mut grid: Vec<Vec<u8>> = input
-        .split('\n')
-        .filter(|l| !l.is_empty())
-        .map(|s| s.to_string().as_bytes().to_vec())
-        .collect();
-
-    let x_max = grid.iter().map(|l| l.len()).max().unwrap() as usize;
-
-    // padd all the lines with ' ' to get a square grid
-    grid.iter_mut().for_each(|line| {
-        let padd_length = x_max - line.len();
-        (0..padd_length).for_each(|_| line.push(b' '));
-    });
-
-    grid
+#[test]
+fn test_parse_input() {
+    let grid = Day19::parse_input("  |  \n  +-A\n").unwrap();
+    assert_eq!((grid.width(), grid.height()), (5, 2));
+    assert_eq!(grid.to_string(), "  |  \n  +-A\n");
 }
 
 #[test]
@@ -190,7 +214,7 @@ fn test_answer1() {
         +B-+  +--+"#,
     );
 
-    assert_eq!(answer1(&input), "ABCDEF".to_string());
+    assert_eq!(Day19::run1(&input).unwrap(), "ABCDEF".to_string());
 }
 
 #[test]
@@ -205,5 +229,5 @@ fn test_answer2() {
         +B-+  +--+"#,
     );
 
-    assert_eq!(answer2(&input), 38);
+    assert_eq!(Day19::run2(&input).unwrap(), 38);
 }
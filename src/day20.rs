@@ -0,0 +1,283 @@
+use crate::common;
+use crate::{ParseError, Solver};
+use nom::types::CompleteStr;
+use nom::{char, do_parse, map_res, named, opt, recognize, tag, tuple};
+use std::collections::BTreeMap;
+use std::ops::AddAssign;
+
+pub struct Day20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Vec3 {
+    x: i64,
+    y: i64,
+    z: i64,
+}
+
+impl Vec3 {
+    fn manhattan(self) -> i64 {
+        self.x.abs() + self.y.abs() + self.z.abs()
+    }
+
+    fn coordinates(self) -> [i64; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+
+impl AddAssign for Vec3 {
+    fn add_assign(&mut self, other: Vec3) {
+        self.x += other.x;
+        self.y += other.y;
+        self.z += other.z;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Particle {
+    p: Vec3,
+    v: Vec3,
+    a: Vec3,
+}
+
+impl Particle {
+    fn step(&mut self) {
+        self.v += self.a;
+        self.p += self.v;
+    }
+
+    // once the position, velocity and acceleration of every axis share the same sign,
+    // the distance to the origin only grows: |p| + t * |v| + t * (t + 1) / 2 * |a|
+    fn is_settled(&self) -> bool {
+        let (p, v, a) = (
+            self.p.coordinates(),
+            self.v.coordinates(),
+            self.a.coordinates(),
+        );
+        (0..3).all(|i| match a[i] {
+            0 => p[i] * v[i] >= 0,
+            a => v[i] * a >= 0 && p[i] * a >= 0,
+        })
+    }
+
+    // first tick after which both particles are at the same position, if there is one
+    fn collision(&self, other: &Particle) -> Option<i64> {
+        let (p, v, a) = (
+            self.p.coordinates(),
+            self.v.coordinates(),
+            self.a.coordinates(),
+        );
+        let (q, w, b) = (
+            other.p.coordinates(),
+            other.v.coordinates(),
+            other.a.coordinates(),
+        );
+
+        // the difference of the positions at time t is dp + t * dv + t * (t + 1) / 2 * da
+        let mut times: Option<Vec<i64>> = None; // None: after any tick
+        for i in 0..3 {
+            let (dp, dv, da) = (p[i] - q[i], v[i] - w[i], a[i] - b[i]);
+            let roots = match integer_roots(da, 2 * dv + da, 2 * dp) {
+                Roots::Always => continue,
+                Roots::Some(roots) => roots,
+            };
+            times = Some(match times {
+                None => roots,
+                Some(times) => times.into_iter().filter(|t| roots.contains(t)).collect(),
+            });
+        }
+
+        match times {
+            None => Some(1),
+            Some(times) => times.into_iter().min(),
+        }
+    }
+}
+
+enum Roots {
+    Always,
+    Some(Vec<i64>),
+}
+
+// positive integer solutions of a * t^2 + b * t + c = 0
+fn integer_roots(a: i64, b: i64, c: i64) -> Roots {
+    let candidates = match (a, b) {
+        (0, 0) if c == 0 => return Roots::Always,
+        (0, 0) => vec![],
+        (0, b) if c % b == 0 => vec![-c / b],
+        (0, _) => vec![],
+        (a, b) => {
+            let discriminant = b * b - 4 * a * c;
+            if discriminant < 0 || discriminant.isqrt().pow(2) != discriminant {
+                vec![]
+            } else {
+                let root = discriminant.isqrt();
+                [-b - root, -b + root]
+                    .iter()
+                    .filter(|n| *n % (2 * a) == 0)
+                    .map(|n| n / (2 * a))
+                    .collect()
+            }
+        }
+    };
+    Roots::Some(candidates.into_iter().filter(|t| *t > 0).collect())
+}
+
+named!(
+    number_parser<CompleteStr, i64>,
+    map_res!(recognize!(tuple!(opt!(char!('-')), nom::digit)), |CompleteStr(s)| s.parse::<i64>())
+);
+
+named!(
+    vec3_parser<CompleteStr, Vec3>,
+    do_parse!(
+        char!('<')
+            >> opt!(nom::space)
+            >> x: number_parser
+            >> char!(',')
+            >> opt!(nom::space)
+            >> y: number_parser
+            >> char!(',')
+            >> opt!(nom::space)
+            >> z: number_parser
+            >> char!('>')
+            >> (Vec3 { x, y, z })
+    )
+);
+
+named!(
+    particle_parser<CompleteStr, Particle>,
+    do_parse!(
+        tag!("p=")
+            >> p: vec3_parser
+            >> tag!(", v=")
+            >> v: vec3_parser
+            >> tag!(", a=")
+            >> a: vec3_parser
+            >> (Particle { p, v, a })
+    )
+);
+
+impl Solver for Day20 {
+    const DAY: u32 = 20;
+    const TITLE: &'static str = "Day 20: Particle Swarm";
+
+    type Input = Vec<Particle>;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn parse_input(input: &str) -> Result<Vec<Particle>, ParseError> {
+        let particles = common::parse_lines(Self::DAY, input, |l| {
+            common::parse_nom(l, particle_parser, "`p=<x,y,z>, v=<x,y,z>, a=<x,y,z>`")
+        })?;
+        if particles.is_empty() {
+            return Err(common::missing(Self::DAY, input, "a particle"));
+        }
+        Ok(particles)
+    }
+
+    fn answer1(particles: &Vec<Particle>) -> usize {
+        let mut particles = particles.clone();
+        while !particles.iter().all(Particle::is_settled) {
+            particles.iter_mut().for_each(Particle::step);
+        }
+
+        // from now on the distances are polynomials of t, compare their coefficients
+        particles
+            .iter()
+            .enumerate()
+            .min_by_key(|(i, p)| (p.a.manhattan(), p.v.manhattan(), p.p.manhattan(), *i))
+            .map(|(i, _)| i)
+            .unwrap()
+    }
+
+    fn answer2(particles: &Vec<Particle>) -> usize {
+        let mut collisions: BTreeMap<i64, Vec<(usize, usize)>> = BTreeMap::new();
+        for (i, first) in particles.iter().enumerate() {
+            for (j, second) in particles.iter().enumerate().skip(i + 1) {
+                if let Some(t) = first.collision(second) {
+                    collisions.entry(t).or_default().push((i, j));
+                }
+            }
+        }
+
+        // a collision only happens if neither particle was destroyed before
+        let mut alive = vec![true; particles.len()];
+        for pairs in collisions.values() {
+            let destroyed: Vec<(usize, usize)> = pairs
+                .iter()
+                .filter(|(i, j)| alive[*i] && alive[*j])
+                .cloned()
+                .collect();
+            for (i, j) in destroyed {
+                alive[i] = false;
+                alive[j] = false;
+            }
+        }
+
+        alive.iter().filter(|a| **a).count()
+    }
+}
+
+#[test]
+fn test_parse_input() {
+    assert_eq!(
+        Day20::parse_input("p=< 3,0,0>, v=<2,-1,0>, a=<-1, 0,0>").unwrap(),
+        vec![Particle {
+            p: Vec3 { x: 3, y: 0, z: 0 },
+            v: Vec3 { x: 2, y: -1, z: 0 },
+            a: Vec3 { x: -1, y: 0, z: 0 },
+        }]
+    );
+    assert_eq!(
+        Day20::parse_input("p=<3,0,0>, v=<2,0>, a=<-1,0,0>")
+            .unwrap_err()
+            .to_string(),
+        "day 20, line 1, column 18: expected `p=<x,y,z>, v=<x,y,z>, a=<x,y,z>`, found `>`"
+    );
+    assert_eq!(Day20::parse_input("").unwrap_err().expected, "a particle");
+}
+
+#[test]
+fn test_collision() {
+    let particles = Day20::parse_input(
+        "p=<-6,0,0>, v=< 3,0,0>, a=< 0,0,0>
+p=<-4,0,0>, v=< 2,0,0>, a=< 0,0,0>
+p=< 3,0,0>, v=<-1,0,0>, a=< 0,0,0>
+p=< 0,0,0>, v=< 0,0,0>, a=< 2,0,0>
+p=<-6,0,0>, v=< 2,0,0>, a=< 0,0,0>",
+    )
+    .unwrap();
+    assert_eq!(particles[0].collision(&particles[1]), Some(2));
+    assert_eq!(particles[0].collision(&particles[2]), None);
+    // after one step, the last particle has moved by 2 and the third one by -1
+    assert_eq!(particles[3].collision(&particles[2]), Some(1));
+    // starting at the same position is not a collision, they must meet after a tick
+    assert_eq!(particles[0].collision(&particles[4]), None);
+    assert_eq!(particles[0].collision(&particles[0]), Some(1));
+}
+
+#[test]
+fn test_answer1() {
+    assert_eq!(
+        Day20::run1(
+            "p=< 3,0,0>, v=< 2,0,0>, a=<-1,0,0>
+p=< 4,0,0>, v=< 0,0,0>, a=<-2,0,0>"
+        )
+        .unwrap(),
+        0
+    );
+}
+
+#[test]
+fn test_answer2() {
+    assert_eq!(
+        Day20::run2(
+            "p=<-6,0,0>, v=< 3,0,0>, a=< 0,0,0>
+p=<-4,0,0>, v=< 2,0,0>, a=< 0,0,0>
+p=<-2,0,0>, v=< 1,0,0>, a=< 0,0,0>
+p=< 3,0,0>, v=<-1,0,0>, a=< 0,0,0>"
+        )
+        .unwrap(),
+        1
+    );
+}
@@ -0,0 +1,176 @@
+use crate::common;
+use crate::error::LineError;
+use crate::grid::Grid;
+use crate::{ParseError, Solver};
+use std::collections::HashMap;
+use std::fmt;
+
+pub struct Day21;
+
+const START: &str = ".#./..#/###";
+
+/// The enhancement rules, with every rotation and flip of their input pattern.
+#[derive(Debug)]
+pub struct Rulebook {
+    rules: HashMap<Grid<bool>, Grid<bool>>,
+}
+
+impl Rulebook {
+    fn new(rules: Vec<(Grid<bool>, Grid<bool>)>) -> Self {
+        let mut rulebook = HashMap::new();
+        for (input, output) in rules {
+            for pattern in input.symmetries() {
+                rulebook.insert(pattern, output.clone());
+            }
+        }
+        Rulebook { rules: rulebook }
+    }
+
+    fn enhance(&self, grid: &Grid<bool>) -> Result<Grid<bool>, MissingRule> {
+        let size = if grid.width().is_multiple_of(2) { 2 } else { 3 };
+        let blocks = grid
+            .split(size)
+            .into_iter()
+            .map(|block| match self.rules.get(&block) {
+                Some(output) => Ok(output.clone()),
+                None => Err(MissingRule(block)),
+            })
+            .collect::<Result<Vec<Grid<bool>>, MissingRule>>()?;
+        Ok(Grid::join(&blocks, grid.width() / size))
+    }
+
+    /// The grid after some iterations, starting from the glider-like pattern.
+    pub fn iterate(&self, iterations: usize) -> Result<Grid<bool>, MissingRule> {
+        let start = parse_pattern(START).unwrap();
+        (0..iterations).try_fold(start, |grid, _| self.enhance(&grid))
+    }
+
+    fn count_on(&self, iterations: usize) -> Option<usize> {
+        let grid = self.iterate(iterations).ok()?;
+        Some(grid.cells().filter(|p| *p).count())
+    }
+}
+
+/// A block of the grid that no rule of the rulebook matches.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingRule(pub Grid<bool>);
+
+impl fmt::Display for MissingRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no rule matches the pattern\n{}", self.0)
+    }
+}
+
+// a pattern like `.#./..#/###`, with all its rows of the same length
+fn parse_pattern(s: &str) -> Result<Grid<bool>, LineError> {
+    let mut rows = vec![];
+    for row in s.split('/') {
+        let pixels = row
+            .char_indices()
+            .map(|(i, c)| match c {
+                '#' => Ok(true),
+                '.' => Ok(false),
+                _ => Err(LineError::new(common::offset(s, row) + i, "`#` or `.`")),
+            })
+            .collect::<Result<Vec<bool>, LineError>>()?;
+        if pixels.len() != s.split('/').count() {
+            return Err(LineError::new(common::offset(s, row), "a square pattern"));
+        }
+        rows.push(pixels);
+    }
+    Ok(Grid::from_rows(rows))
+}
+
+fn parse_rule(line: &str) -> Result<(Grid<bool>, Grid<bool>), LineError> {
+    let arrow = line
+        .find(" => ")
+        .ok_or_else(|| LineError::new(line.find(' ').unwrap_or(line.len()), "` => `"))?;
+    let (input, output) = (&line[..arrow], &line[arrow + 4..]);
+    let shift = |e: LineError| LineError::new(e.offset + arrow + 4, e.expected);
+    let (input, output) = (parse_pattern(input)?, parse_pattern(output).map_err(shift)?);
+
+    // the grid is split in blocks of 2 or 3 pixels, which grow by one
+    match (input.width(), output.width()) {
+        (2, 3) | (3, 4) => Ok((input, output)),
+        (2, _) => Err(LineError::new(arrow + 4, "a 3x3 pattern for a 2x2 one")),
+        (3, _) => Err(LineError::new(arrow + 4, "a 4x4 pattern for a 3x3 one")),
+        _ => Err(LineError::new(0, "a 2x2 or 3x3 pattern")),
+    }
+}
+
+impl Solver for Day21 {
+    const DAY: u32 = 21;
+    const TITLE: &'static str = "Day 21: Fractal Art";
+
+    type Input = Rulebook;
+    type Output1 = Option<usize>;
+    type Output2 = Option<usize>;
+
+    fn parse_input(input: &str) -> Result<Rulebook, ParseError> {
+        common::parse_lines(Self::DAY, input, parse_rule).map(Rulebook::new)
+    }
+
+    // no answer when the rulebook misses a rule
+    fn answer1(rulebook: &Rulebook) -> Option<usize> {
+        rulebook.count_on(5)
+    }
+
+    fn answer2(rulebook: &Rulebook) -> Option<usize> {
+        rulebook.count_on(18)
+    }
+}
+
+#[test]
+fn test_parse_input() {
+    assert_eq!(
+        Day21::parse_input("../.# => ##./#../...")
+            .unwrap()
+            .rules
+            .len(),
+        4 // the 8 rotations and flips of `../.#` are only 4 different patterns
+    );
+    assert_eq!(
+        Day21::parse_input("../.# => ##./#x./...")
+            .unwrap_err()
+            .to_string(),
+        "day 21, line 1, column 15: expected `#` or `.`, found `x./...`"
+    );
+    assert_eq!(
+        Day21::parse_input("../.## => ##./#../...")
+            .unwrap_err()
+            .to_string(),
+        "day 21, line 1, column 4: expected a square pattern, found `.##`"
+    );
+    assert_eq!(
+        Day21::parse_input("../.# => ##/#.")
+            .unwrap_err()
+            .to_string(),
+        "day 21, line 1, column 10: expected a 3x3 pattern for a 2x2 one, found `##/#.`"
+    );
+    assert!(Day21::parse_input(".#./..#/### => ##./#../...").is_err());
+    assert!(Day21::parse_input("#... /..../..../.... => #").is_err());
+}
+
+#[test]
+fn test_iterate() {
+    let rulebook = Day21::parse_input(
+        "../.# => ##./#../...
+.#./..#/### => #..#/..../..../#..#",
+    )
+    .unwrap();
+
+    assert_eq!(
+        rulebook.iterate(1).unwrap().to_string(),
+        "#..#\n....\n....\n#..#\n"
+    );
+    assert_eq!(
+        rulebook.iterate(2).unwrap().to_string(),
+        "##.##.\n#..#..\n......\n##.##.\n#..#..\n......\n"
+    );
+    assert_eq!(rulebook.count_on(2), Some(12));
+
+    // the 2x2 blocks of the second iteration match no rule
+    let missing = rulebook.iterate(3).unwrap_err();
+    assert_eq!(missing.0.to_string(), "##\n#.\n");
+    assert_eq!(Day21::run1("../.# => ##./#../...").unwrap(), None);
+}
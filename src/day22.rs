@@ -0,0 +1,170 @@
+use crate::common;
+use crate::error::LineError;
+use crate::grid::{Dir, Grid, InfiniteGrid, Pixel};
+use crate::{ParseError, Solver};
+
+pub struct Day22;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Node {
+    Clean,
+    Weakened,
+    Infected,
+    Flagged,
+}
+
+impl Pixel for Node {
+    fn to_char(self) -> char {
+        match self {
+            Node::Clean => '.',
+            Node::Weakened => 'W',
+            Node::Infected => '#',
+            Node::Flagged => 'F',
+        }
+    }
+}
+
+// the part 1 virus only knows clean and infected nodes
+fn simple(node: Node) -> Node {
+    match node {
+        Node::Clean => Node::Infected,
+        _ => Node::Clean,
+    }
+}
+
+fn evolved(node: Node) -> Node {
+    match node {
+        Node::Clean => Node::Weakened,
+        Node::Weakened => Node::Infected,
+        Node::Infected => Node::Flagged,
+        Node::Flagged => Node::Clean,
+    }
+}
+
+struct Carrier {
+    map: InfiniteGrid<Node>,
+    position: (isize, isize),
+    dir: Dir,
+    infections: usize,
+}
+
+impl Carrier {
+    // in the middle of the map, facing up
+    fn new(map: &Grid<bool>) -> Self {
+        let nodes = map
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .map(|infected| {
+                        if *infected {
+                            Node::Infected
+                        } else {
+                            Node::Clean
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        Carrier {
+            map: InfiniteGrid::from_grid(Grid::from_rows(nodes), Node::Clean),
+            position: ((map.width() / 2) as isize, (map.height() / 2) as isize),
+            dir: Dir::Up,
+            infections: 0,
+        }
+    }
+
+    fn burst(&mut self, infect: fn(Node) -> Node) {
+        let (x, y) = self.position;
+        let node = self.map.get(x, y);
+        self.dir = match node {
+            Node::Clean => self.dir.turn_left(),
+            Node::Weakened => self.dir,
+            Node::Infected => self.dir.turn_right(),
+            Node::Flagged => self.dir.reverse(),
+        };
+
+        let node = infect(node);
+        if node == Node::Infected {
+            self.infections += 1;
+        }
+        self.map.set(x, y, node);
+        self.position = self.dir.step(self.position);
+    }
+}
+
+fn infections(map: &Grid<bool>, bursts: usize, infect: fn(Node) -> Node) -> usize {
+    let mut carrier = Carrier::new(map);
+    (0..bursts).for_each(|_| carrier.burst(infect));
+    carrier.infections
+}
+
+impl Solver for Day22 {
+    const DAY: u32 = 22;
+    const TITLE: &'static str = "Day 22: Sporifica Virus";
+
+    type Input = Grid<bool>;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn parse_input(input: &str) -> Result<Grid<bool>, ParseError> {
+        let rows = common::parse_lines(Self::DAY, input, |line| {
+            line.char_indices()
+                .map(|(i, c)| match c {
+                    '#' => Ok(true),
+                    '.' => Ok(false),
+                    _ => Err(LineError::new(i, "`#` or `.`")),
+                })
+                .collect::<Result<Vec<bool>, LineError>>()
+        })?;
+
+        if let Some(n) = rows.iter().position(|r| r.len() != rows[0].len()) {
+            let (line_number, line) = input
+                .split('\n')
+                .enumerate()
+                .filter(|(_, l)| !l.is_empty())
+                .nth(n)
+                .unwrap();
+            let error = LineError::new(rows[0].len().min(line.len()), "a row as long as the first");
+            return Err(ParseError::new(Self::DAY, line_number + 1, line, error));
+        }
+        Ok(Grid::from_rows(rows))
+    }
+
+    fn answer1(map: &Grid<bool>) -> usize {
+        infections(map, 10_000, simple)
+    }
+
+    fn answer2(map: &Grid<bool>) -> usize {
+        infections(map, 10_000_000, evolved)
+    }
+}
+
+#[test]
+fn test_burst() {
+    let map = Day22::parse_input("..#\n#..\n...\n").unwrap();
+    let mut carrier = Carrier::new(&map);
+    (0..7).for_each(|_| carrier.burst(simple));
+    assert_eq!(carrier.infections, 5);
+    (7..70).for_each(|_| carrier.burst(simple));
+    assert_eq!(carrier.infections, 41);
+}
+
+#[test]
+fn test_parse_input() {
+    assert_eq!(
+        Day22::parse_input("..#\n#.\n").unwrap_err().to_string(),
+        "day 22, line 2, column 3: expected a row as long as the first, found end of line"
+    );
+}
+
+#[test]
+fn test_answer1() {
+    assert_eq!(Day22::run1("..#\n#..\n...").unwrap(), 5587);
+}
+
+#[test]
+fn test_answer2() {
+    let map = Day22::parse_input("..#\n#..\n...").unwrap();
+    assert_eq!(infections(&map, 100, evolved), 26);
+    assert_eq!(Day22::answer2(&map), 2511944);
+}
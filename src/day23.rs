@@ -0,0 +1,251 @@
+use crate::common;
+use crate::day18::{self, Instruction, Register};
+use crate::error::LineError;
+use crate::vm::{State, Vm};
+use crate::{ParseError, Solver};
+use std::str::FromStr;
+
+pub struct Day23;
+
+// The program counts the composite numbers among b, b + step, ..., c, with this loop:
+// for every b, f is cleared when some d * e == b, and h counts the b where it is.
+// Only b, c and the step depend on the input, and b and c are computed before the
+// loop starts: run the program up to there, then count the composite numbers directly.
+const HOT_LOOP: &[&str] = &[
+    "set f 1", // <- loop start: f = 1 while b looks prime
+    "set d 2", // <- for d in 2..b
+    "set e 2", // <- for e in 2..b
+    "set g d",
+    "mul g e",
+    "sub g b",
+    "jnz g 2", // <- if d * e == b { f = 0 }
+    "set f 0",
+    "sub e -1",
+    "set g e",
+    "sub g b",
+    "jnz g -8",
+    "sub d -1",
+    "set g d",
+    "sub g b",
+    "jnz g -13",
+    "jnz f 2",
+    "sub h -1", // <- h += 1 when b is composite
+    "set g b",
+    "sub g c",
+    "jnz g 2", // <- stop once b == c
+    "jnz 1 3",
+    "sub b -",   // <- b += step, for any step
+    "jnz 1 -23", // <- back to the loop start
+];
+
+// interpreting gives up after this many steps, as the program may never end
+const STEP_LIMIT: usize = 10_000_000;
+
+struct HotLoop {
+    start: usize,
+    step: i64,
+}
+
+impl HotLoop {
+    // check that the program ends with the loop above
+    fn find(instructions: &[Instruction]) -> Option<HotLoop> {
+        let start = instructions.len().checked_sub(HOT_LOOP.len())?;
+        let mut step = None;
+        for (instruction, expected) in instructions[start..].iter().zip(HOT_LOOP) {
+            match instruction {
+                Instruction::Sub('b', Register::Val(s)) if *expected == "sub b -" && *s < 0 => {
+                    step = Some(-s)
+                }
+                _ if instruction.to_string() == *expected => {}
+                _ => return None,
+            }
+        }
+        step.map(|step| HotLoop { start, step })
+    }
+
+    // the number of composite numbers the loop finds, if it stops at all
+    fn count(&self, b: i64, c: i64) -> Option<usize> {
+        if b < 2 || c < b || (c - b) % self.step != 0 {
+            return None;
+        }
+        let count = (b..=c)
+            .step_by(self.step as usize)
+            .filter(|n| is_composite(*n))
+            .count();
+        Some(count)
+    }
+}
+
+fn is_composite(n: i64) -> bool {
+    (2..).take_while(|d| d * d <= n).any(|d| n % d == 0)
+}
+
+// run the program until it ends or reaches `stop`, None after too many steps
+fn run_until(program: &mut Vm, stop: Option<usize>) -> Option<State> {
+    for _ in 0..STEP_LIMIT {
+        if stop.is_some_and(|stop| program.pc() == stop as i64) {
+            return Some(State::Running);
+        }
+        match program.step(&mut ()) {
+            State::Running => {}
+            state => return Some(state),
+        }
+    }
+    None
+}
+
+impl Solver for Day23 {
+    const DAY: u32 = 23;
+    const TITLE: &'static str = "Day 23: Coprocessor Conflagration";
+
+    type Input = Vec<Instruction>;
+    type Output1 = Option<usize>;
+    type Output2 = Option<i64>;
+
+    // the coprocessor has no sound card: no `snd` nor `rcv`
+    fn parse_input(input: &str) -> Result<Vec<Instruction>, ParseError> {
+        common::parse_lines(Self::DAY, input, |line| {
+            match Instruction::from_str(line)? {
+                Instruction::Send(_) | Instruction::Recover(_) => {
+                    let opcode = common::words(line).next().map_or(0, |(offset, _)| offset);
+                    Err(LineError::new(opcode, "an instruction of the coprocessor"))
+                }
+                instruction => Ok(instruction),
+            }
+        })
+    }
+
+    // no answer for the programs that do not end
+    fn answer1(instructions: &Vec<Instruction>) -> Option<usize> {
+        let bytecode = day18::compile(instructions);
+        let mut program = Vm::new(&bytecode);
+        run_until(&mut program, None)?;
+
+        let count = instructions
+            .iter()
+            .zip(program.executed())
+            .filter(|(instruction, _)| matches!(instruction, Instruction::Mul(_, _)))
+            .map(|(_, count)| count)
+            .sum();
+        Some(count)
+    }
+
+    // the fast path for the loop above, or interpreting the programs that are not it
+    fn answer2(instructions: &Vec<Instruction>) -> Option<i64> {
+        let hot_loop = HotLoop::find(instructions);
+
+        let bytecode = day18::compile(instructions);
+        let mut program = Vm::new(&bytecode);
+        program.set_register("a", 1);
+        if let Some(hot_loop) = &hot_loop {
+            run_until(&mut program, Some(hot_loop.start))?;
+            let (b, c) = (program.register("b"), program.register("c"));
+            if program.pc() == hot_loop.start as i64 {
+                if let Some(count) = hot_loop.count(b, c) {
+                    return Some(count as i64);
+                }
+            }
+        }
+
+        run_until(&mut program, None)?;
+        Some(program.register("h"))
+    }
+}
+
+#[cfg(test)]
+fn program(b: i64, b_factor: i64, b_offset: i64, c_offset: i64) -> String {
+    format!(
+        "set b {}
+set c b
+jnz a 2
+jnz 1 5
+mul b {}
+sub b {}
+set c b
+sub c {}
+set f 1
+set d 2
+set e 2
+set g d
+mul g e
+sub g b
+jnz g 2
+set f 0
+sub e -1
+set g e
+sub g b
+jnz g -8
+sub d -1
+set g d
+sub g b
+jnz g -13
+jnz f 2
+sub h -1
+set g b
+sub g c
+jnz g 2
+jnz 1 3
+sub b -17
+jnz 1 -23",
+        b, b_factor, -b_offset, -c_offset
+    )
+}
+
+#[test]
+fn test_answer1() {
+    // d and e both go through 2..57
+    assert_eq!(
+        Day23::run1(&program(57, 100, 100_000, 17_000)).unwrap(),
+        Some(55 * 55)
+    );
+    assert_eq!(Day23::run1("jnz 1 0").unwrap(), None);
+}
+
+#[test]
+fn test_parse_input() {
+    let error = Day23::parse_input("set a 1\n  snd a").unwrap_err();
+    assert_eq!((error.line, error.column), (2, 3));
+    assert_eq!(error.expected, "an instruction of the coprocessor");
+    assert!(Day23::parse_input("rcv a").is_err());
+}
+
+#[test]
+fn test_answer2() {
+    assert_eq!(
+        Day23::run2(&program(57, 100, 100_000, 17_000)).unwrap(),
+        Some(915)
+    );
+
+    // small enough to be interpreted: 57, 74 and 91 are all composite
+    let input = program(57, 1, 0, 34);
+    let instructions = Day23::parse_input(&input).unwrap();
+    let bytecode = day18::compile(&instructions);
+    let mut interpreted = Vm::new(&bytecode);
+    interpreted.set_register("a", 1);
+    interpreted.run(&mut ());
+    assert_eq!(interpreted.register("h"), 3);
+    assert_eq!(Day23::answer2(&instructions), Some(3));
+
+    // other programs are interpreted
+    assert_eq!(Day23::run2("set a 1\njgz a 2").unwrap(), Some(0));
+    assert_eq!(Day23::run2("set h 5\nsub b -2\njnz 1 2").unwrap(), Some(5));
+}
+
+#[test]
+fn test_hot_loop() {
+    let instructions = Day23::parse_input(&program(57, 100, 100_000, 17_000)).unwrap();
+    let hot_loop = HotLoop::find(&instructions).unwrap();
+    assert_eq!((hot_loop.start, hot_loop.step), (8, 17));
+    assert!(HotLoop::find(&instructions[..30]).is_none());
+    assert!(HotLoop::find(&instructions[2..]).is_some());
+
+    // the same end, with a different body
+    let input = program(57, 100, 100_000, 17_000).replace("jnz g -8", "jnz g -7");
+    let instructions = Day23::parse_input(&input).unwrap();
+    assert!(HotLoop::find(&instructions).is_none());
+
+    // a loop that never meets c
+    let hot_loop = HotLoop { start: 8, step: 17 };
+    assert_eq!(hot_loop.count(57, 57 + 34), Some(3));
+    assert_eq!(hot_loop.count(57, 57 + 35), None);
+}
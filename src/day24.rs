@@ -0,0 +1,204 @@
+use crate::common;
+use crate::error::LineError;
+use crate::{ParseError, Solver};
+use petgraph::graph::{NodeIndex, UnGraph};
+use petgraph::visit::EdgeRef;
+use std::collections::HashMap;
+use std::fmt;
+
+pub struct Day24;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Component(u32, u32);
+
+impl Component {
+    fn strength(self) -> u32 {
+        self.0 + self.1
+    }
+}
+
+/// The components as a multigraph: one node per number of pins, one edge per component.
+#[derive(Debug)]
+pub struct Ports {
+    graph: UnGraph<u32, Component>,
+    zero: Option<NodeIndex>,
+}
+
+/// A chain of components starting from the 0 pin port, each one turned to match the previous.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Bridge {
+    pub components: Vec<Component>,
+}
+
+impl Bridge {
+    pub fn strength(&self) -> u32 {
+        self.components.iter().map(|c| c.strength()).sum()
+    }
+}
+
+impl fmt::Display for Bridge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let components: Vec<String> = self
+            .components
+            .iter()
+            .map(|c| format!("{}/{}", c.0, c.1))
+            .collect();
+        write!(f, "{}", components.join("--"))
+    }
+}
+
+impl Ports {
+    fn new(components: Vec<Component>) -> Self {
+        let mut graph = UnGraph::new_undirected();
+        let mut nodes: HashMap<u32, NodeIndex> = HashMap::new();
+        for component in components {
+            let a = *nodes
+                .entry(component.0)
+                .or_insert_with(|| graph.add_node(component.0));
+            let b = *nodes
+                .entry(component.1)
+                .or_insert_with(|| graph.add_node(component.1));
+            graph.add_edge(a, b, component);
+        }
+        Ports {
+            graph,
+            zero: nodes.get(&0).cloned(),
+        }
+    }
+
+    /// The best bridge according to `key`, computed from its length and its strength.
+    pub fn best_bridge<K: Ord>(&self, key: impl Fn(usize, u32) -> K) -> Bridge {
+        let mut search = Search {
+            ports: self,
+            used: vec![false; self.graph.edge_count()],
+            current: vec![],
+            best: (key(0, 0), vec![]),
+            key,
+        };
+        if let Some(zero) = self.zero {
+            search.extend(zero, 0);
+        }
+        Bridge {
+            components: search.best.1,
+        }
+    }
+
+    pub fn strongest(&self) -> Bridge {
+        self.best_bridge(|_, strength| strength)
+    }
+
+    pub fn longest(&self) -> Bridge {
+        self.best_bridge(|length, strength| (length, strength))
+    }
+}
+
+// depth first search of every bridge, keeping the best one seen so far
+struct Search<'a, K, F> {
+    ports: &'a Ports,
+    used: Vec<bool>, // by edge index
+    current: Vec<Component>,
+    best: (K, Vec<Component>),
+    key: F,
+}
+
+impl<'a, K: Ord, F: Fn(usize, u32) -> K> Search<'a, K, F> {
+    fn extend(&mut self, port: NodeIndex, strength: u32) {
+        let key = (self.key)(self.current.len(), strength);
+        if key > self.best.0 {
+            self.best = (key, self.current.clone());
+        }
+
+        let graph = &self.ports.graph;
+        let edges: Vec<_> = graph
+            .edges(port)
+            .map(|e| (e.id(), e.target(), e.source()))
+            .collect();
+        for (edge, target, source) in edges {
+            if self.used[edge.index()] {
+                continue;
+            }
+            // the edges of an undirected graph can be seen from either end
+            let next = if source == port { target } else { source };
+            let component = Component(graph[port], graph[next]);
+
+            self.used[edge.index()] = true;
+            self.current.push(component);
+            self.extend(next, strength + component.strength());
+            self.current.pop();
+            self.used[edge.index()] = false;
+        }
+    }
+}
+
+fn parse_component(line: &str) -> Result<Component, LineError> {
+    let mut ports = line.splitn(2, '/');
+    let mut port = || {
+        let s = ports
+            .next()
+            .ok_or_else(|| LineError::new(line.len(), "`/`"))?;
+        s.parse::<u32>()
+            .map_err(|_| LineError::new(common::offset(line, s), "a number of pins"))
+    };
+    Ok(Component(port()?, port()?))
+}
+
+impl Solver for Day24 {
+    const DAY: u32 = 24;
+    const TITLE: &'static str = "Day 24: Electromagnetic Moat";
+
+    type Input = Ports;
+    type Output1 = u32;
+    type Output2 = u32;
+
+    fn parse_input(input: &str) -> Result<Ports, ParseError> {
+        common::parse_lines(Self::DAY, input, parse_component).map(Ports::new)
+    }
+
+    fn answer1(ports: &Ports) -> u32 {
+        ports.strongest().strength()
+    }
+
+    fn answer2(ports: &Ports) -> u32 {
+        ports.longest().strength()
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "0/2
+2/2
+2/3
+3/4
+3/5
+0/1
+10/1
+9/10";
+
+#[test]
+fn test_parse_input() {
+    assert_eq!(parse_component("10/1"), Ok(Component(10, 1)));
+    assert_eq!(
+        Day24::parse_input("0/2\n2-2\n").unwrap_err().to_string(),
+        "day 24, line 2, column 1: expected a number of pins, found `2-2`"
+    );
+    assert_eq!(
+        Day24::parse_input("0/x").unwrap_err().to_string(),
+        "day 24, line 1, column 3: expected a number of pins, found `x`"
+    );
+}
+
+#[test]
+fn test_bridges() {
+    let ports = Day24::parse_input(EXAMPLE).unwrap();
+    assert_eq!(ports.strongest().to_string(), "0/1--1/10--10/9");
+    assert_eq!(ports.longest().to_string(), "0/2--2/2--2/3--3/5");
+}
+
+#[test]
+fn test_answer1() {
+    assert_eq!(Day24::run1(EXAMPLE).unwrap(), 31);
+}
+
+#[test]
+fn test_answer2() {
+    assert_eq!(Day24::run2(EXAMPLE).unwrap(), 19);
+}
@@ -0,0 +1,389 @@
+use crate::common;
+use crate::error::LineError;
+use crate::grid::Dir;
+use crate::{ParseError, Solver};
+use nom::types::CompleteStr;
+use nom::{
+    alt, call, char, do_parse, many1, map_res, multispace, named, named_args, one_of, return_error,
+    tag,
+};
+use std::collections::VecDeque;
+use std::fmt;
+
+pub struct Day25;
+
+/// What to do when reading a value in a state.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Rule<S> {
+    write: bool,
+    direction: Dir,
+    next: S, // the name of the state once parsed, then its index in the blueprint
+}
+
+// the blueprint as written, the states referred to by their names
+struct Text {
+    start: char,
+    steps: usize,
+    states: Vec<(char, [Rule<char>; 2])>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Blueprint {
+    start: usize,
+    steps: usize,
+    names: Vec<char>,
+    states: Vec<[Rule<usize>; 2]>, // the rules when reading 0 and 1, by state index
+}
+
+impl Blueprint {
+    #[cfg(test)]
+    fn rules(&self, name: char) -> Option<&[Rule<usize>; 2]> {
+        let state = self.names.iter().position(|n| *n == name)?;
+        Some(&self.states[state])
+    }
+}
+
+named!(
+    state_parser<CompleteStr, char>,
+    one_of!("ABCDEFGHIJKLMNOPQRSTUVWXYZ")
+);
+
+named!(
+    value_parser<CompleteStr, bool>,
+    alt!(char!('0') => { |_| false } | char!('1') => { |_| true })
+);
+
+named!(
+    direction_parser<CompleteStr, Dir>,
+    alt!(tag!("left") => { |_| Dir::Left } | tag!("right") => { |_| Dir::Right })
+);
+
+named_args!(
+    rule_parser(value: char)<CompleteStr, Rule<char>>,
+    do_parse!(
+        multispace
+            >> tag!("If the current value is ")
+            >> char!(value)
+            >> char!(':')
+            >> multispace
+            >> tag!("- Write the value ")
+            >> write: value_parser
+            >> char!('.')
+            >> multispace
+            >> tag!("- Move one slot to the ")
+            >> direction: direction_parser
+            >> char!('.')
+            >> multispace
+            >> tag!("- Continue with state ")
+            >> next: state_parser
+            >> char!('.')
+            >> (Rule {
+                write,
+                direction,
+                next
+            })
+    )
+);
+
+// once in a state block, an error is reported where it happens
+named!(
+    state_rules_parser<CompleteStr, (char, [Rule<char>; 2])>,
+    do_parse!(
+        multispace
+            >> tag!("In state ")
+            >> name: return_error!(state_parser)
+            >> return_error!(char!(':'))
+            >> zero: return_error!(call!(rule_parser, '0'))
+            >> one: return_error!(call!(rule_parser, '1'))
+            >> (name, [zero, one])
+    )
+);
+
+named!(
+    blueprint_parser<CompleteStr, Text>,
+    do_parse!(
+        tag!("Begin in state ")
+            >> start: state_parser
+            >> char!('.')
+            >> multispace
+            >> tag!("Perform a diagnostic checksum after ")
+            >> steps: map_res!(nom::digit, |CompleteStr(s)| s.parse::<usize>())
+            >> tag!(" steps.")
+            >> states: many1!(state_rules_parser)
+            >> (Text {
+                start,
+                steps,
+                states
+            })
+    )
+);
+
+/// A Turing machine, on a tape of 0s growing on both sides as the head moves.
+#[derive(Debug)]
+pub struct Machine<'a> {
+    blueprint: &'a Blueprint,
+    tape: VecDeque<bool>,
+    origin: usize, // index in the tape of the slot 0
+    head: isize,
+    state: usize,
+}
+
+impl<'a> Machine<'a> {
+    pub fn new(blueprint: &'a Blueprint) -> Self {
+        Machine {
+            blueprint,
+            tape: VecDeque::from(vec![false]),
+            origin: 0,
+            head: 0,
+            state: blueprint.start,
+        }
+    }
+
+    pub fn state(&self) -> char {
+        self.blueprint.names[self.state]
+    }
+
+    /// The position of the head, the slot it started on being 0.
+    pub fn head(&self) -> isize {
+        self.head
+    }
+
+    pub fn get(&self, slot: isize) -> bool {
+        let index = slot + self.origin as isize;
+        index >= 0 && self.tape.get(index as usize) == Some(&true)
+    }
+
+    /// The values from `radius` slots left of the head to `radius` slots right of it.
+    pub fn window(&self, radius: isize) -> Vec<bool> {
+        (self.head - radius..=self.head + radius)
+            .map(|slot| self.get(slot))
+            .collect()
+    }
+
+    pub fn step(&mut self) {
+        let index = (self.head + self.origin as isize) as usize;
+        let rule = self.blueprint.states[self.state][self.tape[index] as usize];
+        self.tape[index] = rule.write;
+        self.state = rule.next;
+        self.head = rule.direction.step((self.head, 0)).0;
+
+        // extend the tape when the head leaves it
+        if self.head + (self.origin as isize) < 0 {
+            self.tape.push_front(false);
+            self.origin += 1;
+        } else if self.head + self.origin as isize == self.tape.len() as isize {
+            self.tape.push_back(false);
+        }
+    }
+
+    pub fn run(&mut self, steps: usize) {
+        (0..steps).for_each(|_| self.step());
+    }
+
+    /// The number of 1s on the tape.
+    pub fn checksum(&self) -> usize {
+        self.tape.iter().filter(|v| **v).count()
+    }
+}
+
+// like the puzzle, but centered on the head: `... 0  1  0 [0] 1  0  0 ... (state A)`
+impl<'a> fmt::Display for Machine<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "...")?;
+        for (i, value) in self.window(3).iter().enumerate() {
+            match i {
+                3 => write!(f, "[{}]", *value as u8)?,
+                _ => write!(f, " {} ", *value as u8)?,
+            }
+        }
+        write!(f, "... (state {})", self.state())
+    }
+}
+
+impl Solver for Day25 {
+    const DAY: u32 = 25;
+    const TITLE: &'static str = "Day 25: The Halting Problem";
+
+    type Input = Blueprint;
+    type Output1 = usize;
+    type Output2 = ();
+
+    fn parse_input(input: &str) -> Result<Blueprint, ParseError> {
+        let text = common::parse_nom_input(
+            Self::DAY,
+            input,
+            blueprint_parser,
+            "the blueprint of a Turing machine",
+        )?;
+
+        // the error at the nth line containing some text, on its offset
+        let error_at = |text: &str, nth: usize, shift: usize, expected| {
+            let (n, line) = input
+                .split('\n')
+                .enumerate()
+                .filter(|(_, l)| l.contains(text))
+                .nth(nth)
+                .unwrap();
+            let offset = line.find(text).unwrap() + shift;
+            Err(ParseError::new(
+                Self::DAY,
+                n + 1,
+                line,
+                LineError::new(offset, expected),
+            ))
+        };
+
+        let mut names: Vec<char> = vec![];
+        for (name, _) in &text.states {
+            if names.contains(name) {
+                let header = format!("In state {}:", name);
+                return error_at(&header, 1, 9, "a state defined once");
+            }
+            names.push(*name);
+        }
+
+        // every state the machine can go to must have its rules
+        let index = |name: char| names.iter().position(|n| *n == name);
+        let next_states = text
+            .states
+            .iter()
+            .flat_map(|(_, r)| r.iter().map(|r| r.next));
+        let undefined = Some(text.start)
+            .into_iter()
+            .chain(next_states)
+            .find(|name| index(*name).is_none());
+        if let Some(name) = undefined {
+            return error_at(&format!("state {}.", name), 0, 6, "a state with rules");
+        }
+
+        let resolve = |rule: &Rule<char>| Rule {
+            write: rule.write,
+            direction: rule.direction,
+            next: index(rule.next).unwrap(),
+        };
+        Ok(Blueprint {
+            start: index(text.start).unwrap(),
+            steps: text.steps,
+            states: text
+                .states
+                .iter()
+                .map(|(_, [zero, one])| [resolve(zero), resolve(one)])
+                .collect(),
+            names,
+        })
+    }
+
+    fn answer1(blueprint: &Blueprint) -> usize {
+        let mut machine = Machine::new(blueprint);
+        machine.run(blueprint.steps);
+        machine.checksum()
+    }
+
+    // there is no part two, the last star is given for the 49 others
+    fn answer2(_: &Blueprint) {}
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "Begin in state A.
+Perform a diagnostic checksum after 6 steps.
+
+In state A:
+  If the current value is 0:
+    - Write the value 1.
+    - Move one slot to the right.
+    - Continue with state B.
+  If the current value is 1:
+    - Write the value 0.
+    - Move one slot to the left.
+    - Continue with state B.
+
+In state B:
+  If the current value is 0:
+    - Write the value 1.
+    - Move one slot to the left.
+    - Continue with state A.
+  If the current value is 1:
+    - Write the value 1.
+    - Move one slot to the right.
+    - Continue with state A.
+";
+
+#[test]
+fn test_parse_input() {
+    let blueprint = Day25::parse_input(EXAMPLE).unwrap();
+    assert_eq!((blueprint.start, blueprint.steps), (0, 6));
+    assert_eq!(
+        blueprint.rules('B').unwrap()[1],
+        Rule {
+            write: true,
+            direction: Dir::Right,
+            next: 0
+        }
+    );
+
+    assert_eq!(
+        Day25::parse_input(&EXAMPLE.replace(
+            "slot to the left.\n    - Continue with state A",
+            "slot to the top.\n    - Continue with state A"
+        ))
+        .unwrap_err()
+        .to_string(),
+        "day 25, line 17, column 28: expected the blueprint of a Turing machine, found `top.`"
+    );
+    assert_eq!(
+        Day25::parse_input("Begin in state a.")
+            .unwrap_err()
+            .to_string(),
+        "day 25, line 1, column 16: expected the blueprint of a Turing machine, found `a.`"
+    );
+    assert_eq!(
+        Day25::parse_input(&EXAMPLE.replace("Continue with state B.", "Continue with state C."))
+            .unwrap_err()
+            .to_string(),
+        "day 25, line 8, column 27: expected a state with rules, found `C.`"
+    );
+    assert_eq!(
+        Day25::parse_input(&EXAMPLE.replace("In state B:", "In state A:"))
+            .unwrap_err()
+            .to_string(),
+        "day 25, line 14, column 10: expected a state defined once, found `A:`"
+    );
+}
+
+#[test]
+fn test_machine() {
+    let blueprint = Day25::parse_input(EXAMPLE).unwrap();
+    let mut machine = Machine::new(&blueprint);
+    let mut steps = vec![machine.to_string()];
+    for _ in 0..6 {
+        machine.step();
+        steps.push(machine.to_string());
+    }
+    assert_eq!(
+        steps,
+        vec![
+            "... 0  0  0 [0] 0  0  0 ... (state A)",
+            "... 0  0  1 [0] 0  0  0 ... (state B)",
+            "... 0  0  0 [1] 1  0  0 ... (state A)",
+            "... 0  0  0 [0] 0  1  0 ... (state B)",
+            "... 0  0  0 [0] 1  0  1 ... (state A)",
+            "... 0  0  1 [1] 0  1  0 ... (state B)",
+            "... 0  1  1 [0] 1  0  0 ... (state A)",
+        ]
+    );
+    assert_eq!(machine.head(), 0);
+    assert_eq!(machine.window(1), vec![true, false, true]);
+}
+
+#[test]
+fn test_answer1() {
+    assert_eq!(Day25::run1(EXAMPLE).unwrap(), 3);
+}
+
+#[test]
+fn test_answer2() {
+    assert_eq!(
+        Day25::run2(EXAMPLE).map(crate::Answer::from),
+        Ok(crate::Answer::None)
+    );
+}
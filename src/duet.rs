@@ -0,0 +1,1006 @@
+//! The Duet programs of day 18: a debugger, with either semantics of `snd` and `rcv`,
+//! and networks of any number of programs sending values to each other.
+
+use crate::day18::{Instruction, Queues, Register, Sound};
+use crate::vm::{Bytecode, Cmp, Fault, Io, Op, Receive, State, Vm};
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+
+/// What `snd` and `rcv` mean.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Semantics {
+    Sound,  // part 1: a single program playing sounds
+    Queues, // part 2: two programs sending values to each other
+}
+
+/// Where to stop when running the programs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Breakpoint {
+    Pc(i64),                  // before running the instruction at this index
+    Register(char, Cmp, i64), // when the condition becomes true
+}
+
+impl Breakpoint {
+    fn holds(self, vm: &Vm) -> bool {
+        match self {
+            Breakpoint::Pc(pc) => vm.pc() == pc,
+            Breakpoint::Register(name, cmp, value) => {
+                cmp.apply(vm.register(&name.to_string()), value)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Breakpoint::Pc(pc) => write!(f, "pc == {}", pc),
+            Breakpoint::Register(name, cmp, value) => write!(f, "{} {} {}", name, cmp, value),
+        }
+    }
+}
+
+/// What happened when stepping through the programs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    Stepped(usize),           // a program ran an instruction
+    Blocked(usize),           // a program waits on `rcv` with an empty queue
+    Terminated(usize),        // a program jumped out of its instructions, or recovered a sound
+    Breakpoint(usize, usize), // a program hit the breakpoint with this index
+    Deadlock,                 // every program left is blocked
+    Finished,                 // every program is terminated
+}
+
+/// The programs of a duet, run one instruction at a time.
+///
+/// Like `answer2`, a program runs until it is blocked, then the next one takes over.
+pub struct Debugger<'a> {
+    instructions: &'a [Instruction],
+    semantics: Semantics,
+    programs: Vec<Vm<'a>>,
+    states: Vec<State>,
+    queues: Vec<VecDeque<i64>>, // the queue of the values received by every program
+    sound: Sound,
+    current: usize,
+    names: Vec<char>, // the registers used by the instructions
+    breakpoints: Vec<Breakpoint>,
+}
+
+impl<'a> Debugger<'a> {
+    pub fn new(
+        instructions: &'a [Instruction],
+        bytecode: &'a Bytecode,
+        semantics: Semantics,
+    ) -> Self {
+        let count = match semantics {
+            Semantics::Sound => 1,
+            Semantics::Queues => 2,
+        };
+        let programs = (0..count)
+            .map(|id| {
+                let mut vm = Vm::new(bytecode);
+                if semantics == Semantics::Queues {
+                    vm.set_register("p", id as i64);
+                }
+                vm
+            })
+            .collect();
+
+        let mut names: Vec<char> = instructions
+            .iter()
+            .flat_map(|instruction| match instruction {
+                Instruction::Send(rx) => vec![var(rx)],
+                Instruction::Recover(rx) => vec![Some(*rx)],
+                Instruction::Set(rx, ry)
+                | Instruction::Add(rx, ry)
+                | Instruction::Sub(rx, ry)
+                | Instruction::Mul(rx, ry)
+                | Instruction::Mod(rx, ry) => vec![Some(*rx), var(ry)],
+                Instruction::Jump(rx, ry) | Instruction::JumpNotZero(rx, ry) => {
+                    vec![var(rx), var(ry)]
+                }
+            })
+            .flatten()
+            .collect();
+        names.sort();
+        names.dedup();
+
+        Debugger {
+            instructions,
+            semantics,
+            programs,
+            states: vec![State::Running; count],
+            queues: vec![VecDeque::new(); count],
+            sound: Sound::default(),
+            current: 0,
+            names,
+            breakpoints: vec![],
+        }
+    }
+
+    /// The number of programs: 1 with the sound semantics, 2 with the queues.
+    pub fn count(&self) -> usize {
+        self.programs.len()
+    }
+
+    pub fn program(&self, id: usize) -> &Vm<'a> {
+        &self.programs[id]
+    }
+
+    pub fn queue(&self, id: usize) -> &VecDeque<i64> {
+        &self.queues[id]
+    }
+
+    /// The last sound played, with the part 1 semantics.
+    pub fn sound(&self) -> Option<i64> {
+        self.sound.last
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> usize {
+        self.breakpoints.push(breakpoint);
+        self.breakpoints.len() - 1
+    }
+
+    pub fn remove_breakpoint(&mut self, index: usize) -> Option<Breakpoint> {
+        if index < self.breakpoints.len() {
+            Some(self.breakpoints.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// The programs waiting on `rcv` for a value that was not sent yet.
+    pub fn blocked(&self) -> Vec<usize> {
+        (0..self.programs.len())
+            .filter(|id| self.states[*id] == State::Blocked && self.queues[*id].is_empty())
+            .collect()
+    }
+
+    // the next program able to run, starting with the current one
+    fn runnable(&self) -> Option<usize> {
+        let count = self.programs.len();
+        (0..count)
+            .map(|i| (self.current + i) % count)
+            .find(|id| match self.states[*id] {
+                State::Running => true,
+                State::Blocked => !self.queues[*id].is_empty(),
+                State::Terminated => false,
+            })
+    }
+
+    /// Run a single instruction of the current program, or of the next one if it is blocked.
+    pub fn step(&mut self) -> Event {
+        let id = match self.runnable() {
+            Some(id) => id,
+            None if self.states.iter().all(|s| *s == State::Terminated) => return Event::Finished,
+            None => return Event::Deadlock,
+        };
+        self.current = id;
+
+        let program = &mut self.programs[id];
+        let state = match self.semantics {
+            Semantics::Sound => program.step(&mut self.sound),
+            Semantics::Queues => {
+                let (first, second) = self.queues.split_at_mut(1);
+                let (inbox, outbox) = match id {
+                    0 => (&mut first[0], &mut second[0]),
+                    _ => (&mut second[0], &mut first[0]),
+                };
+                program.step(&mut Queues { inbox, outbox })
+            }
+        };
+        self.states[id] = state;
+
+        match state {
+            State::Running => Event::Stepped(id),
+            State::Blocked => Event::Blocked(id),
+            State::Terminated => Event::Terminated(id),
+        }
+    }
+
+    /// Run until a breakpoint is hit, or until no program can run anymore.
+    pub fn run(&mut self) -> Event {
+        loop {
+            let id = self.runnable();
+            let before: Vec<bool> = match id {
+                Some(id) => self
+                    .breakpoints
+                    .iter()
+                    .map(|b| b.holds(&self.programs[id]))
+                    .collect(),
+                None => vec![],
+            };
+
+            match self.step() {
+                Event::Stepped(id) => {
+                    let program = &self.programs[id];
+                    let hit = self
+                        .breakpoints
+                        .iter()
+                        .zip(before)
+                        .position(|(b, before)| match b {
+                            Breakpoint::Pc(_) => b.holds(program),
+                            Breakpoint::Register(..) => !before && b.holds(program),
+                        });
+                    if let Some(index) = hit {
+                        return Event::Breakpoint(id, index);
+                    }
+                }
+                Event::Blocked(_) | Event::Terminated(_) => {}
+                event => return event,
+            }
+        }
+    }
+
+    /// The registers used by the instructions, e.g. `a=1 b=0 p=1`.
+    pub fn registers(&self, id: usize) -> String {
+        let program = &self.programs[id];
+        let registers: Vec<String> = self
+            .names
+            .iter()
+            .map(|name| format!("{}={}", name, program.register(&name.to_string())))
+            .collect();
+        registers.join(" ")
+    }
+
+    /// The instruction a program runs next, if any.
+    pub fn instruction(&self, id: usize) -> Option<&Instruction> {
+        let pc = self.programs[id].pc();
+        if pc < 0 {
+            None
+        } else {
+            self.instructions.get(pc as usize)
+        }
+    }
+
+    /// The instructions around the next one of a program, which is marked with `>`.
+    pub fn listing(&self, id: usize, radius: usize) -> String {
+        let pc = self.programs[id].pc();
+        let start = (pc - radius as i64).max(0) as usize;
+        let end = ((pc + radius as i64 + 1).max(0) as usize).min(self.instructions.len());
+        (start..end)
+            .map(|i| {
+                let marker = if i as i64 == pc { '>' } else { ' ' };
+                format!("{} {:3}  {}\n", marker, i, self.instructions[i])
+            })
+            .collect()
+    }
+}
+
+fn var(register: &Register) -> Option<char> {
+    match register {
+        Register::Var(name) => Some(*name),
+        Register::Val(_) => None,
+    }
+}
+
+// one line per program: `program 1: blocked at 4 `rcv a`, queue []`
+impl<'a> fmt::Display for Debugger<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (id, program) in self.programs.iter().enumerate() {
+            let state = match self.states[id] {
+                State::Blocked if self.queues[id].is_empty() => "blocked",
+                State::Terminated => "terminated",
+                _ => "running",
+            };
+            write!(f, "program {}: {}", id, state)?;
+            if let Some(instruction) = self.instruction(id) {
+                write!(f, " at {} `{}`", program.pc(), instruction)?;
+            }
+            match self.semantics {
+                Semantics::Sound => match self.sound.last {
+                    Some(sound) => writeln!(f, ", last sound {}", sound)?,
+                    None => writeln!(f, ", no sound played")?,
+                },
+                Semantics::Queues => writeln!(f, ", queue {:?}", self.queues[id])?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Who receives the values sent by every program.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Topology {
+    Ring,      // to the next program, the last one sending to the first
+    Pairwise,  // between 0 and 1, 2 and 3, ..., the values of an odd last program being lost
+    Broadcast, // to every other program
+}
+
+impl Topology {
+    fn targets(self, id: usize, count: usize) -> Vec<usize> {
+        match self {
+            Topology::Ring => vec![(id + 1) % count],
+            Topology::Pairwise => Some(id ^ 1).filter(|t| *t < count).into_iter().collect(),
+            Topology::Broadcast => (0..count).filter(|t| *t != id).collect(),
+        }
+    }
+}
+
+/// How the programs of a network are run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Cooperative, // on the current thread, each program running until it is blocked
+    Threads,     // one thread per program, sending the values through channels
+}
+
+/// How many values a program sent and received.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Stats {
+    pub sent: usize,
+    pub received: usize,
+}
+
+/// A program at the end of a run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub state: State,
+    pub pc: i64,
+    pub registers: Vec<i64>, // from `a` to `z`
+    pub pending: Vec<i64>,   // the values sent to the program and not received
+    pub stats: Stats,
+    pub steps: usize,         // the instructions it ran
+    pub fault: Option<Fault>, // why it stopped at `pc`, when terminated there
+}
+
+impl Snapshot {
+    fn new(
+        program: &Vm,
+        state: State,
+        pending: impl IntoIterator<Item = i64>,
+        stats: Stats,
+    ) -> Self {
+        let pending: Vec<i64> = pending.into_iter().collect();
+        // a blocked program with values to receive was only waiting for its turn
+        let state = match state {
+            State::Blocked if !pending.is_empty() => State::Running,
+            state => state,
+        };
+        Snapshot {
+            state,
+            pc: program.pc(),
+            registers: program.registers().to_vec(),
+            pending,
+            stats,
+            steps: program.executed().iter().sum(),
+            fault: program.fault(),
+        }
+    }
+
+    pub fn register(&self, name: char) -> i64 {
+        self.registers[(name as u8 - b'a') as usize]
+    }
+}
+
+// `blocked at 21, sent 127, received 127, pending [], a=1 p=2`, without the registers at 0
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.state {
+            State::Running => write!(f, "running at {}", self.pc)?,
+            State::Blocked => write!(f, "blocked at {}", self.pc)?,
+            State::Terminated => match self.fault {
+                Some(fault) => write!(f, "stopped at {}: {}", self.pc, fault)?,
+                None => write!(f, "terminated")?,
+            },
+        }
+        write!(
+            f,
+            ", sent {}, received {}, pending {:?}",
+            self.stats.sent, self.stats.received, self.pending
+        )?;
+        let registers: Vec<String> = (b'a'..=b'z')
+            .zip(&self.registers)
+            .filter(|(_, value)| **value != 0)
+            .map(|(name, value)| format!("{}={}", name as char, value))
+            .collect();
+        if !registers.is_empty() {
+            write!(f, ", {}", registers.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Why a network stopped, with the last state of every program.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Terminated(Vec<Snapshot>), // every program jumped out of its instructions
+    Deadlock(Vec<Snapshot>),   // the programs left are blocked with nothing to receive
+    StepLimit(Vec<Snapshot>),  // the programs ran as many instructions as allowed
+    Fault(Vec<Snapshot>),      // some programs stopped on a fault, whatever the others did
+}
+
+impl Outcome {
+    pub fn programs(&self) -> &[Snapshot] {
+        match self {
+            Outcome::Terminated(programs)
+            | Outcome::Deadlock(programs)
+            | Outcome::StepLimit(programs)
+            | Outcome::Fault(programs) => programs,
+        }
+    }
+
+    fn new(programs: Vec<Snapshot>, limited: bool) -> Self {
+        if limited {
+            Outcome::StepLimit(programs)
+        } else if programs.iter().any(|p| p.fault.is_some()) {
+            Outcome::Fault(programs)
+        } else if programs.iter().all(|p| p.state == State::Terminated) {
+            Outcome::Terminated(programs)
+        } else {
+            Outcome::Deadlock(programs)
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Terminated(_) => writeln!(f, "every program terminated")?,
+            Outcome::Deadlock(_) => writeln!(f, "deadlock")?,
+            Outcome::StepLimit(_) => writeln!(f, "step limit reached")?,
+            Outcome::Fault(_) => writeln!(f, "a program stopped on a fault")?,
+        }
+        for (id, program) in self.programs().iter().enumerate() {
+            writeln!(f, "program {}: {}", id, program)?;
+        }
+        Ok(())
+    }
+}
+
+/// Programs running the same instructions, with their id in register `p`.
+pub struct Network<'a> {
+    bytecode: &'a Bytecode,
+    count: usize,
+    topology: Topology,
+    step_limit: usize,
+}
+
+impl<'a> Network<'a> {
+    pub fn new(bytecode: &'a Bytecode, count: usize, topology: Topology) -> Self {
+        Network {
+            bytecode,
+            count,
+            topology,
+            step_limit: usize::MAX,
+        }
+    }
+
+    /// Stop once the programs ran this many instructions altogether.
+    pub fn step_limit(mut self, steps: usize) -> Self {
+        self.step_limit = steps;
+        self
+    }
+
+    fn program(&self, id: usize) -> Vm<'a> {
+        let mut vm = Vm::new(self.bytecode);
+        vm.set_register("p", id as i64);
+        vm
+    }
+
+    // whether the next step of a program surely runs no instruction, so that it can be taken
+    // at the step limit
+    fn idle(&self, program: &Vm, nothing_to_receive: bool) -> bool {
+        let instr = usize::try_from(program.pc())
+            .ok()
+            .and_then(|pc| self.bytecode.code.get(pc));
+        match instr {
+            _ if program.fault().is_some() => true,
+            None => true,
+            Some(instr) => {
+                nothing_to_receive && instr.condition.is_none() && matches!(instr.op, Op::Rcv(_))
+            }
+        }
+    }
+
+    /// Run until every program is terminated or blocked with nothing to receive.
+    ///
+    /// Programs only see the values they receive: when each one receives from a single
+    /// other program, as in a ring or in pairs, both modes end the same way, unless the step
+    /// limit is reached. With more senders, the threads may interleave their values.
+    pub fn run(&self, mode: Mode) -> Outcome {
+        match mode {
+            Mode::Cooperative => self.cooperative(),
+            Mode::Threads => self.threads(),
+        }
+    }
+
+    fn cooperative(&self) -> Outcome {
+        let mut programs: Vec<Vm> = (0..self.count).map(|id| self.program(id)).collect();
+        let targets: Vec<Vec<usize>> = (0..self.count)
+            .map(|id| self.topology.targets(id, self.count))
+            .collect();
+        let mut queues = vec![VecDeque::new(); self.count];
+        let mut states = vec![State::Running; self.count];
+        let mut stats = vec![Stats::default(); self.count];
+        let mut steps = 0;
+
+        let limited = 'run: loop {
+            let mut progress = false;
+            for id in 0..self.count {
+                let runnable = match states[id] {
+                    State::Running => true,
+                    State::Blocked => !queues[id].is_empty(),
+                    State::Terminated => false,
+                };
+                if !runnable {
+                    continue;
+                }
+                progress = true;
+
+                // like `Vm::run`, counting the steps
+                let mut io = Mailboxes {
+                    id,
+                    targets: &targets[id],
+                    queues: &mut queues,
+                    stats: &mut stats[id],
+                };
+                loop {
+                    let idle = self.idle(&programs[id], io.queues[id].is_empty());
+                    if steps == self.step_limit && !idle {
+                        break 'run true;
+                    }
+                    states[id] = programs[id].step(&mut io);
+                    if states[id] != State::Running {
+                        break;
+                    }
+                    steps += 1;
+                }
+            }
+            if !progress {
+                break false;
+            }
+        };
+
+        let snapshots = programs
+            .iter()
+            .zip(states)
+            .zip(queues.into_iter().zip(stats))
+            .map(|((program, state), (queue, stats))| Snapshot::new(program, state, queue, stats))
+            .collect();
+        Outcome::new(snapshots, limited)
+    }
+
+    fn threads(&self) -> Outcome {
+        let (senders, receivers): (Vec<_>, Vec<_>) = (0..self.count).map(|_| channel()).unzip();
+        let shared = Shared {
+            scheduler: Mutex::new(Scheduler {
+                states: vec![State::Running; self.count],
+                pending: vec![0; self.count],
+                over: false,
+                limited: false,
+            }),
+            wake_up: Condvar::new(),
+            steps: AtomicUsize::new(0),
+            step_limit: self.step_limit,
+        };
+
+        // the programs are joined before reading their queues, as anyone can still send to them
+        let ended: Vec<_> = thread::scope(|scope| {
+            let shared = &shared;
+            let handles: Vec<_> = receivers
+                .into_iter()
+                .enumerate()
+                .map(|(id, inbox)| {
+                    let outboxes = self
+                        .topology
+                        .targets(id, self.count)
+                        .into_iter()
+                        .map(|t| (t, senders[t].clone()))
+                        .collect();
+                    let mut io = Channels {
+                        id,
+                        inbox,
+                        outboxes,
+                        shared,
+                        stats: Stats::default(),
+                        reserved: false,
+                    };
+                    let mut program = self.program(id);
+                    scope.spawn(move || {
+                        let state = loop {
+                            // like the cooperative mode, only counting the instructions run
+                            if self.idle(&program, false) {
+                                break State::Terminated;
+                            }
+                            // an unconditional `rcv` waits for a value before taking a step
+                            io.reserved = !self.idle(&program, true);
+                            if io.reserved && !shared.reserve_step() {
+                                let mut scheduler = shared.scheduler.lock().unwrap();
+                                scheduler.limit_reached();
+                                shared.wake_up.notify_all();
+                                break State::Running;
+                            }
+                            match program.step(&mut io) {
+                                State::Running => {}
+                                State::Terminated => {
+                                    // on a fault
+                                    shared.release_step();
+                                    break State::Terminated;
+                                }
+                                // without a step reserved
+                                State::Blocked => break State::Blocked,
+                            }
+                        };
+                        if state == State::Terminated {
+                            let mut scheduler = shared.scheduler.lock().unwrap();
+                            scheduler.states[id] = State::Terminated;
+                            scheduler.update();
+                            shared.wake_up.notify_all();
+                        }
+                        (program, io.inbox, io.stats)
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        let scheduler = shared.scheduler.into_inner().unwrap();
+        let snapshots = ended
+            .iter()
+            .zip(scheduler.states)
+            .map(|((program, inbox, stats), state)| {
+                Snapshot::new(program, state, inbox.try_iter(), *stats)
+            })
+            .collect();
+        Outcome::new(snapshots, scheduler.limited)
+    }
+}
+
+// the queues of all the programs of a cooperative network
+struct Mailboxes<'m> {
+    id: usize,
+    targets: &'m [usize],
+    queues: &'m mut [VecDeque<i64>],
+    stats: &'m mut Stats,
+}
+
+impl<'m> Io for Mailboxes<'m> {
+    fn send(&mut self, value: i64) {
+        for target in self.targets {
+            self.queues[*target].push_back(value);
+        }
+        self.stats.sent += 1;
+    }
+
+    fn receive(&mut self, _: i64) -> Receive {
+        match self.queues[self.id].pop_front() {
+            Some(value) => {
+                self.stats.received += 1;
+                Receive::Value(value)
+            }
+            None => Receive::Wait,
+        }
+    }
+}
+
+// What the threads know of each other. The values are sent and received with the lock held,
+// so a program cannot be seen blocked while a value is on its way to it.
+struct Shared {
+    scheduler: Mutex<Scheduler>,
+    wake_up: Condvar,
+    steps: AtomicUsize, // the instructions run by all the programs, or about to be
+    step_limit: usize,
+}
+
+impl Shared {
+    // make room for one more instruction, unless the limit is reached
+    fn reserve_step(&self) -> bool {
+        self.steps
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |steps| {
+                (steps < self.step_limit).then_some(steps + 1)
+            })
+            .is_ok()
+    }
+
+    // for an instruction that did not run after all
+    fn release_step(&self) {
+        self.steps.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+struct Scheduler {
+    states: Vec<State>,
+    pending: Vec<usize>, // the values sent to every program and not received yet
+    over: bool,          // no program can run anymore
+    limited: bool,       // or is allowed to
+}
+
+impl Scheduler {
+    fn update(&mut self) {
+        self.over = self
+            .states
+            .iter()
+            .zip(&self.pending)
+            .all(|(state, pending)| match state {
+                State::Running => false,
+                State::Blocked => *pending == 0,
+                State::Terminated => true,
+            });
+    }
+
+    fn limit_reached(&mut self) {
+        self.limited = true;
+        self.over = true;
+    }
+}
+
+struct Channels<'s> {
+    id: usize,
+    inbox: Receiver<i64>,
+    outboxes: Vec<(usize, Sender<i64>)>,
+    shared: &'s Shared,
+    stats: Stats,
+    reserved: bool, // whether a step is reserved for the instruction being run
+}
+
+impl<'s> Io for Channels<'s> {
+    fn send(&mut self, value: i64) {
+        let mut scheduler = self.shared.scheduler.lock().unwrap();
+        for (target, outbox) in &self.outboxes {
+            outbox
+                .send(value)
+                .expect("the programs outlive their inboxes");
+            scheduler.pending[*target] += 1;
+        }
+        self.stats.sent += 1;
+        self.shared.wake_up.notify_all();
+    }
+
+    // Wait for a value, or for every program to be stuck. The step of the `rcv` is only
+    // reserved once there is a value, so that the other programs can run up to the limit
+    // meanwhile, and a program blocked at the limit ends in a deadlock like the others.
+    fn receive(&mut self, _: i64) -> Receive {
+        let mut scheduler = self.shared.scheduler.lock().unwrap();
+        loop {
+            if scheduler.pending[self.id] > 0 {
+                if !self.reserved && !self.shared.reserve_step() {
+                    scheduler.limit_reached();
+                    self.shared.wake_up.notify_all();
+                    return Receive::Wait;
+                }
+                self.reserved = true;
+                let value = self
+                    .inbox
+                    .try_recv()
+                    .expect("the values are counted once sent");
+                scheduler.pending[self.id] -= 1;
+                scheduler.states[self.id] = State::Running;
+                self.stats.received += 1;
+                return Receive::Value(value);
+            }
+            if self.reserved {
+                self.shared.release_step();
+                self.reserved = false;
+            }
+            if scheduler.over {
+                return Receive::Wait;
+            }
+            scheduler.states[self.id] = State::Blocked;
+            scheduler.update();
+            if scheduler.over {
+                self.shared.wake_up.notify_all();
+                return Receive::Wait;
+            }
+            scheduler = self.shared.wake_up.wait(scheduler).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+use crate::day18::{compile, Day18};
+#[cfg(test)]
+use crate::Solver;
+
+#[cfg(test)]
+const EXAMPLE: &str = "snd 1
+snd 2
+snd p
+rcv a
+rcv b
+rcv c
+rcv d";
+
+#[test]
+fn test_step() {
+    let instructions = Day18::parse_input(EXAMPLE).unwrap();
+    let bytecode = compile(&instructions);
+    let mut debugger = Debugger::new(&instructions, &bytecode, Semantics::Queues);
+
+    let events: Vec<Event> = (0..8).map(|_| debugger.step()).collect();
+    assert_eq!(
+        events,
+        vec![
+            Event::Stepped(0),
+            Event::Stepped(0),
+            Event::Stepped(0),
+            Event::Blocked(0),
+            Event::Stepped(1),
+            Event::Stepped(1),
+            Event::Stepped(1),
+            Event::Stepped(1),
+        ]
+    );
+    assert_eq!(debugger.queue(0), &[1, 2, 1]);
+    assert_eq!(debugger.registers(1), "a=1 b=0 c=0 d=0 p=1");
+
+    assert_eq!(debugger.run(), Event::Deadlock);
+    assert_eq!(debugger.blocked(), vec![0, 1]);
+    assert_eq!(
+        debugger.to_string(),
+        "program 0: blocked at 6 `rcv d`, queue []\n\
+         program 1: blocked at 6 `rcv d`, queue []\n"
+    );
+    assert_eq!(debugger.listing(1, 1), "    5  rcv c\n>   6  rcv d\n");
+}
+
+#[test]
+fn test_breakpoints() {
+    let instructions = Day18::parse_input(
+        "set a 1\nadd a 2\nmul a a\nmod a 5\nsnd a\nset a 0\nrcv a\njgz a -1\nset a 1\njgz a -2",
+    )
+    .unwrap();
+    let bytecode = compile(&instructions);
+    let mut debugger = Debugger::new(&instructions, &bytecode, Semantics::Sound);
+
+    debugger.add_breakpoint(Breakpoint::Register('a', Cmp::Gt, 5));
+    debugger.add_breakpoint(Breakpoint::Pc(6));
+    assert_eq!(debugger.run(), Event::Breakpoint(0, 0));
+    assert_eq!(
+        (debugger.program(0).pc(), debugger.registers(0)),
+        (3, "a=9".to_string())
+    );
+    assert_eq!(debugger.run(), Event::Breakpoint(0, 1));
+    assert_eq!(debugger.sound(), Some(4));
+
+    assert_eq!(debugger.remove_breakpoint(1), Some(Breakpoint::Pc(6)));
+    assert_eq!(debugger.run(), Event::Finished);
+    assert_eq!(
+        debugger.to_string(),
+        "program 0: terminated, last sound 4\n"
+    );
+}
+
+#[test]
+fn test_network() {
+    let instructions = Day18::parse_input(EXAMPLE).unwrap();
+    let bytecode = compile(&instructions);
+
+    let sent = |outcome: &Outcome| {
+        outcome
+            .programs()
+            .iter()
+            .map(|p| p.stats.sent)
+            .collect::<Vec<_>>()
+    };
+    let received = |outcome: &Outcome| {
+        outcome
+            .programs()
+            .iter()
+            .map(|p| p.stats.received)
+            .collect::<Vec<_>>()
+    };
+    for mode in [Mode::Cooperative, Mode::Threads] {
+        // every program sends 3 values and wants 4
+        let ring = Network::new(&bytecode, 3, Topology::Ring).run(mode);
+        assert!(matches!(ring, Outcome::Deadlock(_)));
+        assert_eq!((sent(&ring), received(&ring)), (vec![3; 3], vec![3; 3]));
+
+        // a lonely last program never receives anything
+        let pairs = Network::new(&bytecode, 3, Topology::Pairwise).run(mode);
+        assert_eq!(received(&pairs), vec![3, 3, 0]);
+
+        let broadcast = Network::new(&bytecode, 3, Topology::Broadcast).run(mode);
+        assert!(matches!(broadcast, Outcome::Terminated(_)));
+        assert_eq!(received(&broadcast), vec![4; 3]);
+    }
+}
+
+#[test]
+fn test_outcome() {
+    let instructions = Day18::parse_input(EXAMPLE).unwrap();
+    let bytecode = compile(&instructions);
+
+    for mode in [Mode::Cooperative, Mode::Threads] {
+        let outcome = Network::new(&bytecode, 2, Topology::Ring).run(mode);
+        assert_eq!(
+            outcome.to_string(),
+            "deadlock\n\
+             program 0: blocked at 6, sent 3, received 3, pending [], a=1 b=2 c=1\n\
+             program 1: blocked at 6, sent 3, received 3, pending [], a=1 b=2 p=1\n"
+        );
+        assert_eq!(outcome.programs()[1].register('p'), 1);
+
+        let outcome = Network::new(&bytecode, 3, Topology::Broadcast).run(mode);
+        assert_eq!(outcome.programs()[0].pending.len(), 2);
+    }
+
+    // the programs only get to send their values
+    let outcome = Network::new(&bytecode, 2, Topology::Ring)
+        .step_limit(6)
+        .run(Mode::Cooperative);
+    assert!(matches!(outcome, Outcome::StepLimit(_)));
+    assert_eq!(
+        outcome.programs()[1],
+        Snapshot {
+            state: State::Running,
+            pc: 3,
+            registers: outcome.programs()[1].registers.clone(),
+            pending: vec![1, 2, 0],
+            stats: Stats {
+                sent: 3,
+                received: 0
+            },
+            steps: 3,
+            fault: None,
+        }
+    );
+    assert_eq!(outcome.programs()[0].pending, vec![1, 2, 1]);
+
+    let outcome = Network::new(&bytecode, 2, Topology::Ring)
+        .step_limit(6)
+        .run(Mode::Threads);
+    assert!(matches!(outcome, Outcome::StepLimit(_)));
+}
+
+#[test]
+fn test_step_limit() {
+    let instructions = Day18::parse_input(EXAMPLE).unwrap();
+    let bytecode = compile(&instructions);
+
+    let steps = |outcome: &Outcome| outcome.programs().iter().map(|p| p.steps).sum::<usize>();
+    for limit in [0, 5, 9, 11] {
+        for mode in [Mode::Cooperative, Mode::Threads] {
+            let outcome = Network::new(&bytecode, 2, Topology::Ring)
+                .step_limit(limit)
+                .run(mode);
+            assert!(matches!(outcome, Outcome::StepLimit(_)));
+            assert_eq!(steps(&outcome), limit);
+        }
+    }
+
+    // the programs are stuck right at the limit, each one after sending 3 values and receiving them
+    for mode in [Mode::Cooperative, Mode::Threads] {
+        let outcome = Network::new(&bytecode, 2, Topology::Ring)
+            .step_limit(12)
+            .run(mode);
+        assert!(matches!(outcome, Outcome::Deadlock(_)));
+        assert_eq!(steps(&outcome), 12);
+    }
+}
+
+#[test]
+fn test_fault() {
+    let instructions = Day18::parse_input("set a 1\nmod a 0").unwrap();
+    let bytecode = compile(&instructions);
+    let outcome = Network::new(&bytecode, 1, Topology::Ring).run(Mode::Cooperative);
+    assert!(matches!(outcome, Outcome::Fault(_)));
+    assert_eq!(
+        outcome.programs()[0].to_string(),
+        "stopped at 1: modulo by zero, sent 0, received 0, pending [], a=1"
+    );
+
+    let instructions = Day18::parse_input("set a 1\nmod a p\nsnd a").unwrap();
+    let bytecode = compile(&instructions);
+
+    // program 0 divides by its id, and program 1 has nothing to receive anyway
+    for mode in [Mode::Cooperative, Mode::Threads] {
+        let outcome = Network::new(&bytecode, 2, Topology::Ring).run(mode);
+        assert!(matches!(outcome, Outcome::Fault(_)));
+        assert_eq!(outcome.programs()[0].fault, Some(Fault::DivisionByZero));
+        assert_eq!(
+            outcome.to_string(),
+            "a program stopped on a fault\n\
+             program 0: stopped at 1: modulo by zero, sent 0, received 0, pending [0], a=1\n\
+             program 1: terminated, sent 1, received 0, pending [], p=1\n"
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use std::error::Error;
+use std::fmt;
+
+/// A parse failure inside a single line: where it happened and what was expected there.
+/// It becomes a `ParseError` once the day and the line number are known.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineError {
+    pub offset: usize, // in bytes, from the start of the line
+    pub expected: &'static str,
+}
+
+impl LineError {
+    pub fn new(offset: usize, expected: &'static str) -> Self {
+        LineError { offset, expected }
+    }
+}
+
+/// An input that could not be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub day: u32,
+    pub line: usize,   // starting at 1
+    pub column: usize, // starting at 1, in chars
+    pub text: String,  // the offending token, empty at the end of the line
+    pub expected: &'static str,
+}
+
+impl ParseError {
+    pub fn new(day: u32, line_number: usize, line: &str, error: LineError) -> Self {
+        let offset = error.offset.min(line.len());
+
+        // the token starts at the offset and is at least one char long
+        let rest = &line[offset..];
+        let end = rest
+            .char_indices()
+            .skip(1)
+            .find(|(_, c)| c.is_whitespace() || *c == ',')
+            .map_or(rest.len(), |(i, _)| i);
+        let text = &rest[..end];
+
+        ParseError {
+            day,
+            line: line_number,
+            column: line[..offset].chars().count() + 1,
+            text: text.to_string(),
+            expected: error.expected,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "day {:02}, line {}, column {}: expected {}, found ",
+            self.day, self.line, self.column, self.expected
+        )?;
+        if self.text.is_empty() {
+            write!(f, "end of line")
+        } else {
+            write!(f, "`{}`", self.text)
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+#[test]
+fn test_parse_error() {
+    let error = ParseError::new(7, 3, "fwft (7x2) -> ktlj", LineError::new(5, "a weight"));
+    assert_eq!(error.column, 6);
+    assert_eq!(error.text, "(7x2)");
+    assert_eq!(
+        error.to_string(),
+        "day 07, line 3, column 6: expected a weight, found `(7x2)`"
+    );
+
+    let error = ParseError::new(18, 1, "set a", LineError::new(5, "a register or a value"));
+    assert_eq!(
+        error.to_string(),
+        "day 18, line 1, column 6: expected a register or a value, found end of line"
+    );
+}
@@ -0,0 +1,377 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// A cell of a `Grid` that can be drawn as a single char.
+pub trait Pixel: Copy {
+    fn to_char(self) -> char;
+}
+
+impl Pixel for bool {
+    fn to_char(self) -> char {
+        if self {
+            '#'
+        } else {
+            '.'
+        }
+    }
+}
+
+impl Pixel for u8 {
+    fn to_char(self) -> char {
+        self as char
+    }
+}
+
+impl Pixel for char {
+    fn to_char(self) -> char {
+        self
+    }
+}
+
+/// A rectangle of cells, indexed by `(x, y)` from the top left corner.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>, // row by row
+}
+
+impl<T: Copy> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Grid {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    /// Build a grid from its rows, which must all have the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let width = rows.first().map_or(0, |r| r.len());
+        assert!(
+            rows.iter().all(|r| r.len() == width),
+            "the rows of a grid must have the same length"
+        );
+        Grid {
+            width,
+            height: rows.len(),
+            cells: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<T> {
+        if x < self.width && y < self.height {
+            Some(self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = T> + '_ {
+        self.cells.iter().copied()
+    }
+
+    /// A new grid with the cell at `(x, y)` taken from `f(x, y)`.
+    fn from_fn(width: usize, height: usize, f: impl Fn(usize, usize) -> T) -> Self {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| f(x, y))
+            .collect();
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Rotate a quarter turn clockwise.
+    pub fn rotate(&self) -> Self {
+        Grid::from_fn(self.height, self.width, |x, y| {
+            self[(y, self.height - 1 - x)]
+        })
+    }
+
+    /// Mirror left to right.
+    pub fn flip(&self) -> Self {
+        Grid::from_fn(self.width, self.height, |x, y| {
+            self[(self.width - 1 - x, y)]
+        })
+    }
+
+    /// The 8 grids obtained by rotating and flipping this one, possibly with duplicates.
+    pub fn symmetries(&self) -> Vec<Self> {
+        let mut symmetries = vec![self.clone(), self.flip()];
+        for i in 2..8 {
+            symmetries.push(symmetries[i - 2].rotate());
+        }
+        symmetries
+    }
+
+    /// Cut into `size`×`size` blocks, row by row; the sides must be multiples of `size`.
+    pub fn split(&self, size: usize) -> Vec<Self> {
+        assert!(
+            self.width.is_multiple_of(size) && self.height.is_multiple_of(size),
+            "a {}x{} grid cannot be split into blocks of {}",
+            self.width,
+            self.height,
+            size
+        );
+        let mut blocks = vec![];
+        for top in (0..self.height).step_by(size) {
+            for left in (0..self.width).step_by(size) {
+                blocks.push(Grid::from_fn(size, size, |x, y| self[(left + x, top + y)]));
+            }
+        }
+        blocks
+    }
+
+    /// The reverse of `split`: put the blocks back together, `per_row` blocks by row.
+    pub fn join(blocks: &[Self], per_row: usize) -> Self {
+        let (width, height) = (blocks[0].width, blocks[0].height);
+        let rows = blocks.len() / per_row;
+        Grid::from_fn(width * per_row, height * rows, |x, y| {
+            blocks[y / height * per_row + x / width][(x % width, y % height)]
+        })
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) is outside of the grid",
+            x,
+            y
+        );
+        &self.cells[y * self.width + x]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) is outside of the grid",
+            x,
+            y
+        );
+        &mut self.cells[y * self.width + x]
+    }
+}
+
+impl<T: Pixel> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            let line: String = row.iter().map(|p| p.to_char()).collect();
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// A grid without bounds, storing the smallest rectangle holding every cell that was set,
+/// grown as needed in every direction. The other cells have the default value.
+#[derive(Debug, Clone)]
+pub struct InfiniteGrid<T> {
+    grid: Grid<T>,
+    left: isize, // coordinates of the top left corner of the stored grid
+    top: isize,
+    default: T,
+}
+
+impl<T: Copy> InfiniteGrid<T> {
+    pub fn new(default: T) -> Self {
+        InfiniteGrid::from_grid(Grid::new(0, 0, default), default)
+    }
+
+    /// Start with the cells of a grid, its top left corner being at `(0, 0)`.
+    pub fn from_grid(grid: Grid<T>, default: T) -> Self {
+        InfiniteGrid {
+            grid,
+            left: 0,
+            top: 0,
+            default,
+        }
+    }
+
+    fn position(&self, x: isize, y: isize) -> Option<(usize, usize)> {
+        let (x, y) = (x - self.left, y - self.top);
+        if x >= 0 && y >= 0 && (x as usize) < self.grid.width && (y as usize) < self.grid.height {
+            Some((x as usize, y as usize))
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, x: isize, y: isize) -> T {
+        self.position(x, y).map_or(self.default, |p| self.grid[p])
+    }
+
+    pub fn set(&mut self, x: isize, y: isize, value: T) {
+        let position = match self.position(x, y) {
+            Some(position) => position,
+            None => {
+                self.grow(x, y);
+                self.position(x, y).unwrap()
+            }
+        };
+        self.grid[position] = value;
+    }
+
+    // at least double the size of the sides that must move to hold (x, y)
+    fn grow(&mut self, x: isize, y: isize) {
+        let (width, height) = (self.grid.width as isize, self.grid.height as isize);
+        let (mut left, mut top) = (self.left, self.top);
+        let (mut right, mut bottom) = (left + width, top + height);
+        if x < left {
+            left = x.min(left - width.max(4));
+        }
+        if x >= right {
+            right = (x + 1).max(right + width.max(4));
+        }
+        if y < top {
+            top = y.min(top - height.max(4));
+        }
+        if y >= bottom {
+            bottom = (y + 1).max(bottom + height.max(4));
+        }
+
+        let mut grid = Grid::new(
+            (right - left) as usize,
+            (bottom - top) as usize,
+            self.default,
+        );
+        let (dx, dy) = ((self.left - left) as usize, (self.top - top) as usize);
+        for (y, row) in self.grid.rows().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                grid[(x + dx, y + dy)] = *cell;
+            }
+        }
+        self.grid = grid;
+        self.left = left;
+        self.top = top;
+    }
+
+    /// The stored cells, with the coordinates of their top left corner.
+    pub fn bounded(&self) -> (isize, isize, &Grid<T>) {
+        (self.left, self.top, &self.grid)
+    }
+}
+
+/// One of the 4 directions of a grid, the y axis going down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dir {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Dir {
+    pub fn turn_left(self) -> Dir {
+        match self {
+            Dir::Up => Dir::Left,
+            Dir::Right => Dir::Up,
+            Dir::Down => Dir::Right,
+            Dir::Left => Dir::Down,
+        }
+    }
+
+    pub fn turn_right(self) -> Dir {
+        self.turn_left().reverse()
+    }
+
+    pub fn reverse(self) -> Dir {
+        match self {
+            Dir::Up => Dir::Down,
+            Dir::Right => Dir::Left,
+            Dir::Down => Dir::Up,
+            Dir::Left => Dir::Right,
+        }
+    }
+
+    /// The next position when moving in this direction.
+    pub fn step(self, (x, y): (isize, isize)) -> (isize, isize) {
+        match self {
+            Dir::Up => (x, y - 1),
+            Dir::Right => (x + 1, y),
+            Dir::Down => (x, y + 1),
+            Dir::Left => (x - 1, y),
+        }
+    }
+}
+
+#[cfg(test)]
+fn grid_of(s: &str) -> Grid<char> {
+    Grid::from_rows(s.split('/').map(|r| r.chars().collect()).collect())
+}
+
+#[test]
+fn test_rotate_flip() {
+    let grid = grid_of("abc/def");
+    assert_eq!(grid.rotate(), grid_of("da/eb/fc"));
+    assert_eq!(grid.rotate().rotate().rotate().rotate(), grid);
+    assert_eq!(grid.flip(), grid_of("cba/fed"));
+    assert_eq!(grid_of(".#./..#/###").symmetries().len(), 8);
+}
+
+#[test]
+fn test_split_join() {
+    let grid = grid_of("abcd/efgh/ijkl/mnop");
+    let blocks = grid.split(2);
+    assert_eq!(
+        blocks,
+        vec![
+            grid_of("ab/ef"),
+            grid_of("cd/gh"),
+            grid_of("ij/mn"),
+            grid_of("kl/op")
+        ]
+    );
+    assert_eq!(Grid::join(&blocks, 2), grid);
+}
+
+#[test]
+fn test_display() {
+    let grid = Grid::from_rows(vec![vec![true, false], vec![false, false]]);
+    assert_eq!(grid.to_string(), "#.\n..\n");
+}
+
+#[test]
+fn test_infinite_grid() {
+    let mut grid = InfiniteGrid::from_grid(grid_of("ab/cd"), '.');
+    assert_eq!(grid.get(1, 0), 'b');
+    assert_eq!(grid.get(-5, 7), '.');
+
+    grid.set(-2, 3, 'x');
+    grid.set(40, -1, 'y');
+    assert_eq!((grid.get(-2, 3), grid.get(40, -1)), ('x', 'y'));
+    assert_eq!((grid.get(0, 0), grid.get(1, 1)), ('a', 'd'));
+
+    let (left, top, stored) = grid.bounded();
+    assert!(left <= -2 && top <= -1);
+    assert!(stored.width() as isize + left > 40 && stored.height() as isize + top > 3);
+}
+
+#[test]
+fn test_dir() {
+    assert_eq!(Dir::Up.turn_left(), Dir::Left);
+    assert_eq!(Dir::Up.turn_right(), Dir::Right);
+    assert_eq!(Dir::Left.turn_right().turn_right(), Dir::Right);
+    assert_eq!(Dir::Down.reverse(), Dir::Up);
+    assert_eq!(Dir::Down.step((3, 3)), (3, 4));
+}
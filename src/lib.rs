@@ -1,3 +1,7 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::time::{Duration, Instant};
+
 #[macro_use]
 pub mod common;
 pub mod day01;
@@ -19,3 +23,206 @@ pub mod day16;
 pub mod day17;
 pub mod day18;
 pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+pub mod duet;
+pub mod error;
+pub mod grid;
+pub mod vm;
+
+pub use crate::error::ParseError;
+
+/// A puzzle of the calendar: how to parse its input and how to answer both parts.
+pub trait Solver {
+    const DAY: u32;
+    const TITLE: &'static str;
+
+    type Input;
+    type Output1: Into<Answer>;
+    type Output2: Into<Answer>;
+
+    fn parse_input(input: &str) -> Result<Self::Input, ParseError>;
+    fn answer1(input: &Self::Input) -> Self::Output1;
+    fn answer2(input: &Self::Input) -> Self::Output2;
+
+    fn run1(input: &str) -> Result<Self::Output1, ParseError> {
+        Self::parse_input(input).map(|input| Self::answer1(&input))
+    }
+
+    fn run2(input: &str) -> Result<Self::Output2, ParseError> {
+        Self::parse_input(input).map(|input| Self::answer2(&input))
+    }
+}
+
+/// The answer of a part, whatever the type returned by the day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Number(i64),
+    Text(String),
+    None, // the input has no answer for this part, or the day has no such part
+}
+
+macro_rules! answer_from_number {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(n: $t) -> Self {
+                Answer::Number(i64::try_from(n).expect("the answer does not fit in an i64"))
+            }
+        })*
+    };
+}
+
+answer_from_number!(i32, i64, u32, u64, usize);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+/// A day without a second part.
+impl From<()> for Answer {
+    fn from(_: ()) -> Self {
+        Answer::None
+    }
+}
+
+impl<T: Into<Answer>> From<Option<T>> for Answer {
+    fn from(answer: Option<T>) -> Self {
+        answer.map_or(Answer::None, Into::into)
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+            Answer::None => write!(f, "no answer"),
+        }
+    }
+}
+
+/// The answer of one part, and the time it took to compute it from the parsed input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    pub part: u8,
+    pub answer: Answer,
+    pub duration: Duration,
+}
+
+/// The answers of a day, and the time spent parsing its input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Run {
+    pub parse: Duration,
+    pub solutions: Vec<Solution>,
+}
+
+/// Type-erased view of a `Solver`, so that every day can live in the same registry.
+pub trait Puzzle: Sync {
+    fn day(&self) -> u32;
+    fn title(&self) -> &'static str;
+    fn answer1(&self, input: &str) -> Result<Answer, ParseError>;
+    fn answer2(&self, input: &str) -> Result<Answer, ParseError>;
+
+    /// Parse the input once, then answer the given parts, timing every step.
+    fn run(&self, input: &str, parts: &[u8]) -> Result<Run, ParseError>;
+}
+
+impl<S: Solver + Sync> Puzzle for S {
+    fn day(&self) -> u32 {
+        S::DAY
+    }
+
+    fn title(&self) -> &'static str {
+        S::TITLE
+    }
+
+    fn answer1(&self, input: &str) -> Result<Answer, ParseError> {
+        S::run1(input).map(Into::into)
+    }
+
+    fn answer2(&self, input: &str) -> Result<Answer, ParseError> {
+        S::run2(input).map(Into::into)
+    }
+
+    fn run(&self, input: &str, parts: &[u8]) -> Result<Run, ParseError> {
+        let start = Instant::now();
+        let input = S::parse_input(input)?;
+        let parse = start.elapsed();
+
+        let solutions = parts
+            .iter()
+            .map(|part| {
+                let start = Instant::now();
+                let answer = match part {
+                    1 => S::answer1(&input).into(),
+                    2 => S::answer2(&input).into(),
+                    _ => panic!("there is no part {}", part),
+                };
+                Solution {
+                    part: *part,
+                    answer,
+                    duration: start.elapsed(),
+                }
+            })
+            .collect();
+
+        Ok(Run { parse, solutions })
+    }
+}
+
+/// All the days of the calendar, in order.
+pub const DAYS: &[&dyn Puzzle] = &[
+    &day01::Day01,
+    &day02::Day02,
+    &day03::Day03,
+    &day04::Day04,
+    &day05::Day05,
+    &day06::Day06,
+    &day07::Day07,
+    &day08::Day08,
+    &day09::Day09,
+    &day10::Day10,
+    &day11::Day11,
+    &day12::Day12,
+    &day13::Day13,
+    &day14::Day14,
+    &day15::Day15,
+    &day16::Day16,
+    &day17::Day17,
+    &day18::Day18,
+    &day19::Day19,
+    &day20::Day20,
+    &day21::Day21,
+    &day22::Day22,
+    &day23::Day23,
+    &day24::Day24,
+    &day25::Day25,
+];
+
+/// Find a day in the registry.
+pub fn day(day: u32) -> Option<&'static dyn Puzzle> {
+    DAYS.iter().find(|p| p.day() == day).copied()
+}
+
+#[test]
+fn test_run() {
+    let run = day(1).unwrap().run("1122", &[2, 1]).unwrap();
+    assert_eq!(
+        run.solutions
+            .iter()
+            .map(|s| (s.part, s.answer.clone()))
+            .collect::<Vec<_>>(),
+        vec![(2, Answer::Number(0)), (1, Answer::Number(3))]
+    );
+    assert_eq!(
+        day(7).unwrap().answer1("pbga (66)").unwrap(),
+        Answer::Text(String::from("pbga"))
+    );
+    assert!(day(1).unwrap().run("11x2", &[1]).is_err());
+}
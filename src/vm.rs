@@ -0,0 +1,507 @@
+//! A small virtual machine for the register languages of several days.
+//!
+//! Every day parses its own dialect, then compiles it to `Bytecode`, where the registers
+//! are resolved to slot indices. The days only differ by how they send and receive values.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// The index of a register in the registers of the machine.
+pub type Reg = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operand {
+    Reg(Reg),
+    Val(i64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cmp {
+    Eq,
+    Ne,
+    Gt,
+    Lt,
+    Ge,
+    Le,
+}
+
+impl FromStr for Cmp {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Cmp, ()> {
+        match s {
+            "==" => Ok(Cmp::Eq),
+            "!=" => Ok(Cmp::Ne),
+            ">" => Ok(Cmp::Gt),
+            "<" => Ok(Cmp::Lt),
+            ">=" => Ok(Cmp::Ge),
+            "<=" => Ok(Cmp::Le),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Cmp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Cmp::Eq => "==",
+            Cmp::Ne => "!=",
+            Cmp::Gt => ">",
+            Cmp::Lt => "<",
+            Cmp::Ge => ">=",
+            Cmp::Le => "<=",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl Cmp {
+    pub fn apply(self, a: i64, b: i64) -> bool {
+        match self {
+            Cmp::Eq => a == b,
+            Cmp::Ne => a != b,
+            Cmp::Gt => a > b,
+            Cmp::Lt => a < b,
+            Cmp::Ge => a >= b,
+            Cmp::Le => a <= b,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Set(Reg, Operand),
+    Add(Reg, Operand),
+    Sub(Reg, Operand),
+    Mul(Reg, Operand),
+    Mod(Reg, Operand),
+    Jgz(Operand, Operand), // jump by the second operand if the first one is > 0
+    Jnz(Operand, Operand), // or if it is != 0
+    Snd(Operand),
+    Rcv(Reg),
+}
+
+/// A comparison of two operands, e.g. `a > 1`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Guard {
+    pub left: Operand,
+    pub cmp: Cmp,
+    pub right: Operand,
+}
+
+/// A condition for an instruction to do something, e.g. `if a > 1 and b < 2`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Guard(Guard),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instr {
+    pub op: Op,
+    pub condition: Option<Condition>,
+}
+
+/// A compiled program, with the names of its registers.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Bytecode {
+    pub code: Vec<Instr>,
+    names: Vec<String>,
+}
+
+impl Bytecode {
+    /// The slot of a register, given a new one the first time its name is seen.
+    pub fn register(&mut self, name: &str) -> Reg {
+        match self.slot(name) {
+            Some(slot) => slot,
+            None => {
+                self.names.push(name.to_string());
+                self.names.len() - 1
+            }
+        }
+    }
+
+    pub fn slot(&self, name: &str) -> Option<Reg> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    /// The names of the registers, by slot.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn push(&mut self, op: Op, condition: Option<Condition>) {
+        self.code.push(Instr { op, condition });
+    }
+}
+
+/// What `rcv` does with a register.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Receive {
+    Value(i64), // store a value in it
+    Skip,       // leave it alone
+    Wait,       // block until there is something to receive
+    Halt,       // stop the program
+}
+
+/// How a program talks to the outside world with `snd` and `rcv`.
+pub trait Io {
+    fn send(&mut self, value: i64);
+    /// `current` is the value of the register given to `rcv`.
+    fn receive(&mut self, current: i64) -> Receive;
+}
+
+/// Programs that neither send nor receive anything.
+impl Io for () {
+    fn send(&mut self, _: i64) {
+        panic!("this program cannot send anything");
+    }
+
+    fn receive(&mut self, _: i64) -> Receive {
+        panic!("this program cannot receive anything");
+    }
+}
+
+/// An instruction that cannot be run, stopping the program.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fault {
+    Overflow,       // the result or the jump target does not fit in an i64
+    DivisionByZero, // `mod` by 0
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fault::Overflow => write!(f, "the result does not fit in 64 bits"),
+            Fault::DivisionByZero => write!(f, "modulo by zero"),
+        }
+    }
+}
+
+impl Operand {
+    fn value(self, regs: &[i64]) -> i64 {
+        match self {
+            Operand::Reg(r) => regs[r],
+            Operand::Val(v) => v,
+        }
+    }
+}
+
+impl Condition {
+    fn holds(&self, regs: &[i64]) -> bool {
+        match self {
+            Condition::Guard(g) => g.cmp.apply(g.left.value(regs), g.right.value(regs)),
+            Condition::And(a, b) => a.holds(regs) && b.holds(regs),
+            Condition::Or(a, b) => a.holds(regs) || b.holds(regs),
+        }
+    }
+}
+
+// the register written by `set`, `add`, `sub`, `mul` or `mod`, and its new value
+fn arithmetic(op: Op, regs: &[i64]) -> Result<(Reg, i64), Fault> {
+    let checked = |r: Reg, o: Operand, f: fn(i64, i64) -> Option<i64>| {
+        f(regs[r], o.value(regs))
+            .map(|v| (r, v))
+            .ok_or(Fault::Overflow)
+    };
+    match op {
+        Op::Set(r, o) => Ok((r, o.value(regs))),
+        Op::Add(r, o) => checked(r, o, i64::checked_add),
+        Op::Sub(r, o) => checked(r, o, i64::checked_sub),
+        Op::Mul(r, o) => checked(r, o, i64::checked_mul),
+        Op::Mod(_, o) if o.value(regs) == 0 => Err(Fault::DivisionByZero),
+        Op::Mod(r, o) => checked(r, o, i64::checked_rem),
+        _ => unreachable!("`{:?}` is not an arithmetic operation", op),
+    }
+}
+
+/// Run an instruction on some registers, outside of any program, so it must neither jump
+/// nor send or receive anything. The registers are unchanged on a fault.
+pub fn execute(instr: &Instr, regs: &mut [i64]) -> Result<(), Fault> {
+    if instr.condition.as_ref().is_none_or(|c| c.holds(regs)) {
+        let (r, value) = arithmetic(instr.op, regs)?;
+        regs[r] = value;
+    }
+    Ok(())
+}
+
+/// What happened to a program after running one instruction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum State {
+    Running,
+    Blocked,    // waiting for a value to receive
+    Terminated, // past its last instruction, or on a fault
+}
+
+/// A running program, counting how many times every instruction is executed.
+#[derive(Debug, Clone)]
+pub struct Vm<'a> {
+    bytecode: &'a Bytecode,
+    regs: Vec<i64>,
+    pc: i64,
+    executed: Vec<usize>,
+    fired: Vec<usize>,
+    fault: Option<Fault>,
+}
+
+impl<'a> Vm<'a> {
+    pub fn new(bytecode: &'a Bytecode) -> Self {
+        Vm {
+            bytecode,
+            regs: vec![0; bytecode.names.len()],
+            pc: 0,
+            executed: vec![0; bytecode.code.len()],
+            fired: vec![0; bytecode.code.len()],
+            fault: None,
+        }
+    }
+
+    /// Carry on from `pc` with some registers, the ones created since then being 0.
+    pub fn resume(bytecode: &'a Bytecode, pc: i64, mut registers: Vec<i64>) -> Self {
+        registers.resize(bytecode.names.len(), 0);
+        Vm {
+            regs: registers,
+            pc,
+            ..Vm::new(bytecode)
+        }
+    }
+
+    pub fn into_registers(self) -> Vec<i64> {
+        self.regs
+    }
+
+    pub fn pc(&self) -> i64 {
+        self.pc
+    }
+
+    /// The value of a register, 0 for the ones that the program never uses.
+    pub fn register(&self, name: &str) -> i64 {
+        self.bytecode.slot(name).map_or(0, |slot| self.regs[slot])
+    }
+
+    /// Set a register, if the program uses it.
+    pub fn set_register(&mut self, name: &str, value: i64) {
+        if let Some(slot) = self.bytecode.slot(name) {
+            self.regs[slot] = value;
+        }
+    }
+
+    /// The values of the registers, by slot.
+    pub fn registers(&self) -> &[i64] {
+        &self.regs
+    }
+
+    /// How many times the instruction at every index was executed.
+    pub fn executed(&self) -> &[usize] {
+        &self.executed
+    }
+
+    /// How many times the condition of the instruction at every index held,
+    /// the instructions without one always doing something.
+    pub fn fired(&self) -> &[usize] {
+        &self.fired
+    }
+
+    /// Why the program stopped at `pc`, if it did not just run past its end.
+    pub fn fault(&self) -> Option<Fault> {
+        self.fault
+    }
+
+    fn value(&self, operand: Operand) -> i64 {
+        operand.value(&self.regs)
+    }
+
+    // where a jump by `offset` lands
+    fn target(&self, offset: Operand) -> Result<i64, Fault> {
+        self.pc
+            .checked_add(self.value(offset))
+            .ok_or(Fault::Overflow)
+    }
+
+    pub fn step<I: Io + ?Sized>(&mut self, io: &mut I) -> State {
+        let pc = if self.pc < 0 {
+            usize::MAX
+        } else {
+            self.pc as usize
+        };
+        let bytecode = self.bytecode;
+        let instr = match bytecode.code.get(pc) {
+            Some(instr) if self.fault.is_none() => instr,
+            _ => return State::Terminated,
+        };
+
+        let enabled = instr.condition.as_ref().is_none_or(|c| c.holds(&self.regs));
+        let mut next = Ok(self.pc + 1);
+        if enabled {
+            match instr.op {
+                Op::Jgz(test, offset) => {
+                    if self.value(test) > 0 {
+                        next = self.target(offset);
+                    }
+                }
+                Op::Jnz(test, offset) => {
+                    if self.value(test) != 0 {
+                        next = self.target(offset);
+                    }
+                }
+                Op::Snd(o) => io.send(self.value(o)),
+                Op::Rcv(r) => match io.receive(self.regs[r]) {
+                    Receive::Value(v) => self.regs[r] = v,
+                    Receive::Skip => {}
+                    Receive::Wait => return State::Blocked,
+                    Receive::Halt => {
+                        self.fired[pc] += 1;
+                        self.executed[pc] += 1;
+                        self.pc = -1;
+                        return State::Terminated;
+                    }
+                },
+                op => match arithmetic(op, &self.regs) {
+                    Ok((r, value)) => self.regs[r] = value,
+                    Err(fault) => {
+                        self.fault = Some(fault);
+                        return State::Terminated;
+                    }
+                },
+            }
+        }
+
+        let next = match next {
+            Ok(next) => next,
+            Err(fault) => {
+                self.fault = Some(fault);
+                return State::Terminated;
+            }
+        };
+        if enabled {
+            self.fired[pc] += 1;
+        }
+        self.executed[pc] += 1;
+        self.pc = next;
+        State::Running
+    }
+
+    /// Run until the program is blocked or terminated.
+    pub fn run<I: Io + ?Sized>(&mut self, io: &mut I) -> State {
+        loop {
+            match self.step(io) {
+                State::Running => {}
+                state => return state,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+fn countdown() -> Bytecode {
+    // a = 3; while a != 0 { a -= 1; b += 2 if a > 0 }
+    let mut bytecode = Bytecode::default();
+    let (a, b) = (bytecode.register("a"), bytecode.register("b"));
+    bytecode.push(Op::Set(a, Operand::Val(3)), None);
+    bytecode.push(Op::Sub(a, Operand::Val(1)), None);
+    let guard = Guard {
+        left: Operand::Reg(a),
+        cmp: Cmp::Gt,
+        right: Operand::Val(0),
+    };
+    bytecode.push(Op::Add(b, Operand::Val(2)), Some(Condition::Guard(guard)));
+    bytecode.push(Op::Jnz(Operand::Reg(a), Operand::Val(-2)), None);
+    bytecode
+}
+
+#[test]
+fn test_run() {
+    let bytecode = countdown();
+    assert_eq!(bytecode.names(), &["a", "b"]);
+
+    let mut vm = Vm::new(&bytecode);
+    assert_eq!(vm.run(&mut ()), State::Terminated);
+    assert_eq!(vm.registers(), &[0, 4]);
+    assert_eq!((vm.register("b"), vm.register("z")), (4, 0));
+    assert_eq!(vm.executed(), &[1, 3, 3, 3]);
+    assert_eq!(vm.fired(), &[1, 3, 2, 3]);
+    assert_eq!(vm.pc(), 4);
+
+    // b = 10 if a < 1 or b > 100, with a register created after the first run
+    let registers = vm.into_registers();
+    let mut bytecode = bytecode.clone();
+    let (a, b, c) = (0, 1, bytecode.register("c"));
+    let guard = |left, cmp, right| {
+        Box::new(Condition::Guard(Guard {
+            left: Operand::Reg(left),
+            cmp,
+            right: Operand::Val(right),
+        }))
+    };
+    let condition = Condition::Or(guard(a, Cmp::Lt, 1), guard(b, Cmp::Gt, 100));
+    bytecode.push(Op::Set(c, Operand::Val(10)), Some(condition));
+    let mut vm = Vm::resume(&bytecode, 4, registers);
+    assert_eq!(vm.run(&mut ()), State::Terminated);
+    assert_eq!(vm.registers(), &[0, 4, 10]);
+}
+
+#[test]
+fn test_io() {
+    struct Echo(Vec<i64>);
+    impl Io for Echo {
+        fn send(&mut self, value: i64) {
+            self.0.push(value);
+        }
+        fn receive(&mut self, _: i64) -> Receive {
+            match self.0.pop() {
+                Some(v) => Receive::Value(v * 10),
+                None => Receive::Wait,
+            }
+        }
+    }
+
+    let mut bytecode = Bytecode::default();
+    let a = bytecode.register("a");
+    bytecode.push(Op::Snd(Operand::Val(7)), None);
+    bytecode.push(Op::Rcv(a), None);
+    bytecode.push(Op::Rcv(a), None);
+
+    let mut vm = Vm::new(&bytecode);
+    let mut io = Echo(vec![]);
+    assert_eq!(vm.run(&mut io), State::Blocked);
+    assert_eq!((vm.register("a"), vm.pc()), (70, 2));
+}
+
+#[test]
+fn test_fault() {
+    let mut bytecode = Bytecode::default();
+    let (a, b) = (bytecode.register("a"), bytecode.register("b"));
+    bytecode.push(Op::Set(a, Operand::Val(7)), None);
+    bytecode.push(Op::Mod(a, Operand::Reg(b)), None);
+    bytecode.push(Op::Set(b, Operand::Val(1)), None);
+
+    let mut vm = Vm::new(&bytecode);
+    assert_eq!(vm.run(&mut ()), State::Terminated);
+    assert_eq!((vm.fault(), vm.pc()), (Some(Fault::DivisionByZero), 1));
+    assert_eq!(vm.step(&mut ()), State::Terminated);
+    assert_eq!(vm.registers(), &[7, 0]);
+
+    let mut registers = vec![i64::MAX, 0];
+    let add = Instr {
+        op: Op::Add(a, Operand::Val(1)),
+        condition: None,
+    };
+    assert_eq!(execute(&add, &mut registers), Err(Fault::Overflow));
+    assert_eq!(registers, vec![i64::MAX, 0]);
+    assert_eq!(execute(&bytecode.code[2], &mut registers), Ok(()));
+    assert_eq!(registers, vec![i64::MAX, 1]);
+}
+
+#[test]
+fn test_jump_overflow() {
+    let mut bytecode = Bytecode::default();
+    let a = bytecode.register("a");
+    bytecode.push(Op::Set(a, Operand::Val(1)), None);
+    bytecode.push(Op::Jgz(Operand::Reg(a), Operand::Val(i64::MAX)), None);
+
+    let mut vm = Vm::new(&bytecode);
+    assert_eq!(vm.run(&mut ()), State::Terminated);
+    assert_eq!((vm.fault(), vm.pc()), (Some(Fault::Overflow), 1));
+    assert_eq!(vm.executed(), &[1, 0]);
+}